@@ -31,12 +31,16 @@ impl Error for LoxError {
 #[derive(Debug)]
 pub struct SyntaxError {
     line: usize,
+    column: Option<usize>,
     message: String,
 }
 
 impl Display for SyntaxError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Syntax error: [line {}] {}.", self.line, self.message)
+        match self.column {
+            Some(column) => write!(f, "Syntax error: [line {}, column {}] {}.", self.line, column, self.message),
+            None => write!(f, "Syntax error: [line {}] {}.", self.line, self.message),
+        }
     }
 }
 
@@ -48,7 +52,11 @@ impl Error for SyntaxError {
 
 impl SyntaxError {
     pub fn new(line: usize, message: String) -> Self {
-        SyntaxError { line, message }
+        SyntaxError { line, column: None, message }
+    }
+
+    pub fn with_column(line: usize, column: usize, message: String) -> Self {
+        SyntaxError { line, column: Some(column), message }
     }
     // pub fn error(line: usize, message: String) -> SyntaxError {
     //     let error = SyntaxError { line, message };
@@ -97,6 +105,30 @@ pub fn check_number_operands(operator: &Token, nums: &[&Object]) -> Result<(), R
     Ok(())
 }
 
+pub fn check_integer_operands(operator: &Token, nums: &[&Object]) -> Result<(), RuntimeError> {
+    for num in nums {
+        if !num.is_int() {
+            return Err(RuntimeError::new(
+                operator.clone(),
+                "Operands must be integers.".to_string()
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn check_number_arguments(paren: &Token, arguments: &[Object]) -> Result<(), RuntimeError> {
+    for argument in arguments {
+        if !argument.is_num() {
+            return Err(RuntimeError::new(
+                paren.clone(),
+                "Arguments must be numbers.".to_string()
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub fn check_string_operands(operator: &Token, str_arr: &[&Object]) -> Result<(), RuntimeError> {
     for str in str_arr {
         if !str.is_str() {
@@ -1,10 +1,40 @@
-use crate::error::{check_number_operands, check_string_operands, RuntimeError};
+use std::cmp::Ordering;
+use std::rc::Rc;
+use crate::list;
+use crate::sequence;
+use crate::string;
+use crate::map::{self, Map, MapKey};
+use crate::range::{self, Range};
+use crate::module;
+use crate::error::{check_integer_operands, check_number_operands, check_string_operands, RuntimeError};
 use crate::object::Object;
 use crate::token::*;
 
 pub trait Expr {
     fn eval(&self) -> Result<Object, RuntimeError>;
+    #[allow(dead_code)]
     fn to_string(&self) -> String;
+
+    /// 常量折叠：将只包含字面量的子树在执行前求值为LiteralExpr
+    fn fold(self: Box<Self>) -> Box<dyn Expr>;
+
+    /// 若表达式为常量，返回其值
+    fn constant(&self) -> Option<&Object> {
+        None
+    }
+
+    /// 构造以该表达式为目标的赋值表达式，不能被赋值时返回None
+    fn assign(self: Box<Self>, _value: Box<dyn Expr>) -> Option<Box<dyn Expr>> {
+        None
+    }
+}
+
+/// 对操作数均为常量的表达式求值，求值出错时保留原表达式，使错误在运行时抛出
+fn fold_constant<E: Expr + 'static>(expr: E) -> Box<dyn Expr> {
+    match expr.eval() {
+        Ok(value) => LiteralExpr::new(value),
+        Err(_) => Box::new(expr),
+    }
 }
 
 /// BinaryExpr
@@ -16,11 +46,11 @@ pub struct BinaryExpr {
 
 impl BinaryExpr {
     pub fn new(left: Box<dyn Expr>, operator: Token, right: Box<dyn Expr>) -> Box<Self> {
-        return Box::new(BinaryExpr {
+        Box::new(BinaryExpr {
             left,
             operator,
             right
-        });
+        })
     }
 }
 
@@ -31,39 +61,88 @@ impl Expr for BinaryExpr {
         let operator = &self.operator;
 
         match operator.typ {
+            TokenType::Comma => Ok(right.clone()),
             TokenType::EqualEqual => Ok(Object::new_bool(left == right)),
             TokenType::BangEqual => Ok(Object::new_bool(left != right)),
             TokenType::Greater => {
                 check_number_operands(operator, &[left, right])?;
-                Ok(Object::new_bool(left.num() > right.num()))
+                Ok(Object::new_bool(left.compare(right) == Some(Ordering::Greater)))
             }
             TokenType::GreaterEqual => {
                 check_number_operands(operator, &[left, right])?;
-                Ok(Object::new_bool(left.num() >= right.num()))
+                Ok(Object::new_bool(matches!(left.compare(right), Some(Ordering::Greater | Ordering::Equal))))
             }
             TokenType::Less => {
                 check_number_operands(operator, &[left, right])?;
-                Ok(Object::new_bool(left.num() < right.num()))
+                Ok(Object::new_bool(left.compare(right) == Some(Ordering::Less)))
             }
             TokenType::LessEqual => {
                 check_number_operands(operator, &[left, right])?;
-                Ok(Object::new_bool(left.num() <= right.num()))
+                Ok(Object::new_bool(matches!(left.compare(right), Some(Ordering::Less | Ordering::Equal))))
             }
-            TokenType::Minus => {
-                check_number_operands(operator, &[left, right])?;
-                Ok(Object::Num(left.num() - right.num()))
-            },
+            TokenType::Minus => arithmetic(operator, left, right, i64::checked_sub, |x, y| x - y),
             TokenType::Slash => {
                 check_number_operands(operator, &[left, right])?;
                 Ok(Object::Num(left.num() / right.num()))
             },
-            TokenType::Star => {
+            TokenType::Star => arithmetic(operator, left, right, i64::checked_mul, |x, y| x * y),
+            TokenType::DotDot | TokenType::DotDotEqual => {
+                if !left.is_int() || !right.is_int() {
+                    return Err(RuntimeError::new(
+                        operator.clone(),
+                        "Range bounds must be integers.".to_string()
+                    ));
+                }
+                let inclusive = operator.typ == TokenType::DotDotEqual;
+                Ok(Object::Range(Rc::new(Range::new(left.int(), right.int(), inclusive))))
+            }
+            TokenType::Ampersand => {
+                check_integer_operands(operator, &[left, right])?;
+                Ok(Object::Int(left.int() & right.int()))
+            }
+            TokenType::Pipe => {
+                check_integer_operands(operator, &[left, right])?;
+                Ok(Object::Int(left.int() | right.int()))
+            }
+            TokenType::Caret => {
+                check_integer_operands(operator, &[left, right])?;
+                Ok(Object::Int(left.int() ^ right.int()))
+            }
+            TokenType::LessLess => {
+                check_integer_operands(operator, &[left, right])?;
+                Ok(Object::Int(left.int() << shift_amount(operator, right)?))
+            }
+            TokenType::GreaterGreater => {
+                check_integer_operands(operator, &[left, right])?;
+                Ok(Object::Int(left.int() >> shift_amount(operator, right)?))
+            }
+            TokenType::StarStar => {
                 check_number_operands(operator, &[left, right])?;
-                Ok(Object::Num(left.num() * right.num()))
-            },
+                match (left, right) {
+                    (Object::Int(x), Object::Int(y)) if *y >= 0 => u32::try_from(*y)
+                        .ok()
+                        .and_then(|y| x.checked_pow(y))
+                        .map(Object::Int)
+                        .ok_or_else(|| RuntimeError::new(operator.clone(), "Integer overflow.".to_string())),
+                    _ => Ok(Object::Num(left.num().powf(right.num()))),
+                }
+            }
+            TokenType::Percent => {
+                check_number_operands(operator, &[left, right])?;
+                check_divisor(operator, left, right)?;
+                arithmetic(operator, left, right, floor_mod, |x, y| {
+                    let r = x % y;
+                    if r != 0f64 && (r < 0f64) != (y < 0f64) { r + y } else { r }
+                })
+            }
+            TokenType::TildeSlash => {
+                check_number_operands(operator, &[left, right])?;
+                check_divisor(operator, left, right)?;
+                arithmetic(operator, left, right, floor_div, |x, y| (x / y).floor())
+            }
             TokenType::Plus => {
                 if check_number_operands(operator, &[left, right]).is_ok() {
-                    Ok(Object::Num(left.num() + right.num()))
+                    arithmetic(operator, left, right, i64::checked_add, |x, y| x + y)
                 } else if check_string_operands(operator, &[left, right]).is_ok() {
                     Ok(Object::Str(left.str().to_string() + right.str()))
                 } else {
@@ -80,7 +159,289 @@ impl Expr for BinaryExpr {
     fn to_string(&self) -> String {
         let left = self.left.to_string();
         let right = self.right.to_string();
-        "( ".to_string() + &self.operator.lexeme + " " + &left + " " + &right + " )"
+        "( ".to_string() + self.operator.lexeme() + " " + &left + " " + &right + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        let BinaryExpr { left, operator, right } = *self;
+        let expr = BinaryExpr { left: left.fold(), operator, right: right.fold() };
+        if expr.left.constant().is_some() && expr.right.constant().is_some() {
+            fold_constant(expr)
+        } else {
+            Box::new(expr)
+        }
+    }
+}
+
+/// 算术运算：两个整数之间做溢出检查，与浮点数混合时提升为浮点数
+fn arithmetic(
+    operator: &Token,
+    left: &Object,
+    right: &Object,
+    int_op: fn(i64, i64) -> Option<i64>,
+    num_op: fn(f64, f64) -> f64,
+) -> Result<Object, RuntimeError> {
+    check_number_operands(operator, &[left, right])?;
+    match (left, right) {
+        (Object::Int(x), Object::Int(y)) => int_op(*x, *y).map(Object::Int).ok_or_else(|| {
+            RuntimeError::new(operator.clone(), "Integer overflow.".to_string())
+        }),
+        _ => Ok(Object::Num(num_op(left.num(), right.num()))),
+    }
+}
+
+/// 两个整数之间的除法与取模不允许除数为0，与浮点数混合时按浮点数得到inf或NaN
+fn check_divisor(operator: &Token, dividend: &Object, divisor: &Object) -> Result<(), RuntimeError> {
+    if let (Object::Int(_), Object::Int(0)) = (dividend, divisor) {
+        return Err(RuntimeError::new(operator.clone(), "Division by zero.".to_string()));
+    }
+    Ok(())
+}
+
+/// 移位的位数必须在0到63之间
+fn shift_amount(operator: &Token, amount: &Object) -> Result<u32, RuntimeError> {
+    match amount.int() {
+        x @ 0..=63 => Ok(x as u32),
+        _ => Err(RuntimeError::new(operator.clone(), "Shift amount must be between 0 and 63.".to_string())),
+    }
+}
+
+/// 向下取整的整数除法
+fn floor_div(x: i64, y: i64) -> Option<i64> {
+    let q = x.checked_div(y)?;
+    if x % y != 0 && (x < 0) != (y < 0) { Some(q - 1) } else { Some(q) }
+}
+
+/// 与floor_div对应的取模，结果的符号与除数相同
+fn floor_mod(x: i64, y: i64) -> Option<i64> {
+    // checked_rem在i64::MIN % -1时返回None，但结果0并不溢出
+    if y == -1 {
+        return Some(0);
+    }
+    let r = x.checked_rem(y)?;
+    if r != 0 && (r < 0) != (y < 0) { Some(r + y) } else { Some(r) }
+}
+
+/// ConditionalExpr
+pub struct ConditionalExpr {
+    condition: Box<dyn Expr>,
+    then_branch: Box<dyn Expr>,
+    else_branch: Box<dyn Expr>,
+}
+
+impl ConditionalExpr {
+    pub fn new(condition: Box<dyn Expr>, then_branch: Box<dyn Expr>, else_branch: Box<dyn Expr>) -> Box<Self> {
+        Box::new(ConditionalExpr { condition, then_branch, else_branch })
+    }
+}
+
+impl Expr for ConditionalExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        if self.condition.eval()?.is_true() {
+            self.then_branch.eval()
+        } else {
+            self.else_branch.eval()
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "( ? ".to_string() + &self.condition.to_string() + " " + &self.then_branch.to_string()
+            + " " + &self.else_branch.to_string() + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        let condition = self.condition.fold();
+        match condition.constant() {
+            Some(value) if value.is_true() => self.then_branch.fold(),
+            Some(_) => self.else_branch.fold(),
+            None => ConditionalExpr::new(condition, self.then_branch.fold(), self.else_branch.fold()),
+        }
+    }
+}
+
+/// CallExpr
+pub struct CallExpr {
+    callee: Box<dyn Expr>,
+    paren: Token,
+    arguments: Vec<Box<dyn Expr>>,
+}
+
+impl CallExpr {
+    pub fn new(callee: Box<dyn Expr>, paren: Token, arguments: Vec<Box<dyn Expr>>) -> Box<Self> {
+        Box::new(CallExpr { callee, paren, arguments })
+    }
+}
+
+impl Expr for CallExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        let callee = self.callee.eval()?;
+        let arguments = self.arguments.iter().map(|arg| arg.eval()).collect::<Result<Vec<_>, _>>()?;
+        match callee {
+            Object::Native(native) => native.call(&self.paren, &arguments),
+            _ => Err(RuntimeError::new(
+                self.paren.clone(),
+                "Can only call functions and methods.".to_string()
+            )),
+        }
+    }
+
+    fn to_string(&self) -> String {
+        let arguments = self.arguments.iter().map(|arg| " ".to_string() + &arg.to_string()).collect::<String>();
+        "( call ".to_string() + &self.callee.to_string() + &arguments + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        let arguments = self.arguments.into_iter().map(|arg| arg.fold()).collect();
+        CallExpr::new(self.callee.fold(), self.paren, arguments)
+    }
+}
+
+/// GetExpr
+pub struct GetExpr {
+    object: Box<dyn Expr>,
+    name: Token,
+}
+
+impl GetExpr {
+    pub fn new(object: Box<dyn Expr>, name: Token) -> Box<Self> {
+        Box::new(GetExpr { object, name })
+    }
+}
+
+impl Expr for GetExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        let object = self.object.eval()?;
+        let method = match &object {
+            Object::Module(module) => {
+                return module.member(self.name.lexeme()).ok_or_else(|| RuntimeError::new(
+                    self.name.clone(),
+                    format!("Undefined property '{}'.", self.name.lexeme())
+                ));
+            }
+            Object::List(_) => list::method(self.name.lexeme()),
+            Object::Map(_) => map::method(self.name.lexeme()),
+            Object::Range(_) => range::method(self.name.lexeme()),
+            Object::Str(_) => string::method(self.name.lexeme()),
+            _ => None,
+        };
+        match method {
+            Some(method) => Ok(Object::Native(Rc::new(method.bind(object)))),
+            None => Err(RuntimeError::new(
+                self.name.clone(),
+                format!("Undefined property '{}'.", self.name.lexeme())
+            )),
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "( . ".to_string() + &self.object.to_string() + " " + self.name.lexeme() + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        GetExpr::new(self.object.fold(), self.name)
+    }
+}
+
+/// IndexExpr
+pub struct IndexExpr {
+    object: Box<dyn Expr>,
+    bracket: Token,
+    index: Box<dyn Expr>,
+}
+
+impl IndexExpr {
+    pub fn new(object: Box<dyn Expr>, bracket: Token, index: Box<dyn Expr>) -> Box<Self> {
+        Box::new(IndexExpr { object, bracket, index })
+    }
+}
+
+impl Expr for IndexExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        let object = self.object.eval()?;
+        let index = self.index.eval()?;
+        match (&object, &index) {
+            (Object::List(elements), Object::Range(range)) => {
+                let elements = elements.borrow();
+                let slice = range.slice(&self.bracket, elements.len())?;
+                Ok(Object::new_list(slice.map(|i| elements[i].clone()).collect()))
+            }
+            (Object::List(elements), _) => {
+                let elements = elements.borrow();
+                let i = sequence::index(&self.bracket, "List", elements.len(), &index)?;
+                Ok(elements[i].clone())
+            }
+            (Object::Map(entries), _) => map::get(&self.bracket, &entries.borrow(), index),
+            (Object::Str(string), Object::Range(range)) => {
+                let chars = string.chars().collect::<Vec<_>>();
+                let slice = range.slice(&self.bracket, chars.len())?;
+                Ok(Object::Str(slice.map(|i| chars[i]).collect()))
+            }
+            (Object::Str(string), _) => string::index(&self.bracket, string, &index),
+            _ => Err(RuntimeError::new(
+                self.bracket.clone(),
+                "Only lists, maps and strings can be indexed.".to_string()
+            )),
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "( [] ".to_string() + &self.object.to_string() + " " + &self.index.to_string() + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        IndexExpr::new(self.object.fold(), self.bracket, self.index.fold())
+    }
+
+    fn assign(self: Box<Self>, value: Box<dyn Expr>) -> Option<Box<dyn Expr>> {
+        Some(IndexSetExpr::new(self.object, self.bracket, self.index, value))
+    }
+}
+
+/// IndexSetExpr
+pub struct IndexSetExpr {
+    object: Box<dyn Expr>,
+    bracket: Token,
+    index: Box<dyn Expr>,
+    value: Box<dyn Expr>,
+}
+
+impl IndexSetExpr {
+    pub fn new(object: Box<dyn Expr>, bracket: Token, index: Box<dyn Expr>, value: Box<dyn Expr>) -> Box<Self> {
+        Box::new(IndexSetExpr { object, bracket, index, value })
+    }
+}
+
+impl Expr for IndexSetExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        let object = self.object.eval()?;
+        let index = self.index.eval()?;
+        let value = self.value.eval()?;
+        match &object {
+            Object::List(elements) => {
+                let mut elements = elements.borrow_mut();
+                let i = sequence::index(&self.bracket, "List", elements.len(), &index)?;
+                elements[i] = value.clone();
+                Ok(value)
+            }
+            Object::Map(entries) => {
+                let key = MapKey::new(&self.bracket, index)?;
+                entries.borrow_mut().insert(key, value.clone());
+                Ok(value)
+            }
+            _ => Err(RuntimeError::new(
+                self.bracket.clone(),
+                "Only lists and maps can be indexed.".to_string()
+            )),
+        }
+    }
+
+    fn to_string(&self) -> String {
+        "( []= ".to_string() + &self.object.to_string() + " " + &self.index.to_string()
+            + " " + &self.value.to_string() + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        IndexSetExpr::new(self.object.fold(), self.bracket, self.index.fold(), self.value.fold())
     }
 }
 
@@ -103,6 +464,145 @@ impl Expr for GroupingExpr {
     fn to_string(&self) -> String {
         "( ".to_string() + &self.expression.to_string() + " )"
     }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        let expression = self.expression.fold();
+        if expression.constant().is_some() {
+            expression
+        } else {
+            GroupingExpr::new(expression)
+        }
+    }
+}
+
+/// InterpolationExpr
+pub struct InterpolationExpr {
+    parts: Vec<Box<dyn Expr>>,
+}
+
+impl InterpolationExpr {
+    pub fn new(parts: Vec<Box<dyn Expr>>) -> Box<Self> {
+        Box::new(InterpolationExpr { parts })
+    }
+}
+
+impl Expr for InterpolationExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        let mut string = String::new();
+        for part in &self.parts {
+            string += &part.eval()?.stringify();
+        }
+        Ok(Object::Str(string))
+    }
+
+    fn to_string(&self) -> String {
+        let parts = self.parts.iter().map(|part| part.to_string()).collect::<Vec<_>>();
+        "( interpolate ".to_string() + &parts.join(" ") + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        let parts = self.parts.into_iter().map(|part| part.fold()).collect::<Vec<_>>();
+        let constant = parts.iter().all(|part| part.constant().is_some());
+        let expr = InterpolationExpr { parts };
+        if constant {
+            fold_constant(expr)
+        } else {
+            Box::new(expr)
+        }
+    }
+}
+
+/// ListExpr
+pub struct ListExpr {
+    elements: Vec<Box<dyn Expr>>,
+}
+
+impl ListExpr {
+    pub fn new(elements: Vec<Box<dyn Expr>>) -> Box<Self> {
+        Box::new(ListExpr { elements })
+    }
+}
+
+impl Expr for ListExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        let elements = self.elements.iter().map(|e| e.eval()).collect::<Result<Vec<_>, _>>()?;
+        Ok(Object::new_list(elements))
+    }
+
+    fn to_string(&self) -> String {
+        let elements = self.elements.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        "[ ".to_string() + &elements.join(" ") + " ]"
+    }
+
+    /// 列表是可变的，每次求值都要创建新的列表，因此不能折叠为常量
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        ListExpr::new(self.elements.into_iter().map(|e| e.fold()).collect())
+    }
+}
+
+/// MapExpr
+pub struct MapExpr {
+    brace: Token,
+    entries: Vec<(Box<dyn Expr>, Box<dyn Expr>)>,
+}
+
+impl MapExpr {
+    pub fn new(brace: Token, entries: Vec<(Box<dyn Expr>, Box<dyn Expr>)>) -> Box<Self> {
+        Box::new(MapExpr { brace, entries })
+    }
+}
+
+impl Expr for MapExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        let mut entries = Map::new();
+        for (key, value) in &self.entries {
+            let key = MapKey::new(&self.brace, key.eval()?)?;
+            entries.insert(key, value.eval()?);
+        }
+        Ok(Object::new_map(entries))
+    }
+
+    fn to_string(&self) -> String {
+        let entries = self.entries
+            .iter()
+            .map(|(k, v)| k.to_string() + ": " + &v.to_string())
+            .collect::<Vec<_>>();
+        "{ ".to_string() + &entries.join(" ") + " }"
+    }
+
+    /// map与列表一样是可变的，不能折叠为常量
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        let entries = self.entries.into_iter().map(|(k, v)| (k.fold(), v.fold())).collect();
+        MapExpr::new(self.brace, entries)
+    }
+}
+
+/// VariableExpr
+pub struct VariableExpr {
+    name: Token,
+}
+
+impl VariableExpr {
+    pub fn new(name: Token) -> Box<Self> {
+        Box::new(VariableExpr { name })
+    }
+}
+
+impl Expr for VariableExpr {
+    fn eval(&self) -> Result<Object, RuntimeError> {
+        module::global(self.name.lexeme()).ok_or_else(|| RuntimeError::new(
+            self.name.clone(),
+            format!("Undefined variable '{}'.", self.name.lexeme())
+        ))
+    }
+
+    fn to_string(&self) -> String {
+        self.name.lexeme().to_string()
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        self
+    }
 }
 
 /// LiteralExpr
@@ -124,6 +624,14 @@ impl Expr for LiteralExpr {
     fn to_string(&self) -> String {
         self.value.to_string()
     }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        self
+    }
+
+    fn constant(&self) -> Option<&Object> {
+        Some(&self.value)
+    }
 }
 
 /// UnaryExpr
@@ -134,7 +642,7 @@ pub struct UnaryExpr {
 
 impl UnaryExpr {
     pub fn new(operator: Token, right: Box<dyn Expr>) -> Box<Self> {
-        return Box::new(UnaryExpr { operator, right })
+        Box::new(UnaryExpr { operator, right })
     }
 }
 
@@ -145,30 +653,52 @@ impl Expr for UnaryExpr {
         match self.operator.typ {
             TokenType::Minus => {
                 check_number_operands(&self.operator, &[&right])?;
-                Ok(Object::Num(-right.num()))
+                match right {
+                    Object::Int(x) => x.checked_neg().map(Object::Int).ok_or_else(|| {
+                        RuntimeError::new(self.operator.clone(), "Integer overflow.".to_string())
+                    }),
+                    _ => Ok(Object::Num(-right.num())),
+                }
             },
             TokenType::Bang => Ok(Object::new_bool(!right.is_true())),
+            TokenType::Tilde => {
+                check_integer_operands(&self.operator, &[&right])?;
+                Ok(Object::Int(!right.int()))
+            }
             _ => Ok(Object::Nil)
         }
     }
 
     fn to_string(&self) -> String {
-        "( ".to_string() + &self.operator.lexeme + " " + &self.right.to_string() + " )"
+        "( ".to_string() + self.operator.lexeme() + " " + &self.right.to_string() + " )"
+    }
+
+    fn fold(self: Box<Self>) -> Box<dyn Expr> {
+        let UnaryExpr { operator, right } = *self;
+        let expr = UnaryExpr { operator, right: right.fold() };
+        if expr.right.constant().is_some() {
+            fold_constant(expr)
+        } else {
+            Box::new(expr)
+        }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use std::rc::Rc;
     use crate::expr::{BinaryExpr, Expr, GroupingExpr, LiteralExpr, UnaryExpr};
     use crate::object::Object;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
     use crate::token::{Token, TokenType};
 
     fn create_binary() -> BinaryExpr {
-        let expr = BinaryExpr {
+        BinaryExpr {
             left: Box::new(UnaryExpr {
                 operator: Token::new(TokenType::Minus, "-", None, 1),
                 right: Box::new(LiteralExpr {
-                    value: Object::Num(123 as f64)
+                    value: Object::Num(123f64)
                 })
             }),
             operator: Token::new(TokenType::Star, "*", None, 1),
@@ -177,8 +707,7 @@ mod tests {
                     value: Object::Num(45.67)
                 }),
             }),
-        };
-        return expr;
+        }
     }
 
     #[test]
@@ -192,4 +721,110 @@ mod tests {
         assert!(result.is_ok());
         println!("{}", result.ok().unwrap())
     }
+
+    fn parse(source: &str) -> Box<dyn Expr> {
+        Parser::new(Scanner::new(Rc::from(source))).parse().unwrap()
+    }
+
+    /// 解析、折叠并求值，返回结果或错误的文本
+    pub(crate) fn eval(source: &str) -> Result<String, String> {
+        parse(source).fold().eval().map(|x| x.to_string()).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_fold() {
+        let expr = parse("2 * 3 + 1").fold();
+        assert_eq!(expr.constant(), Some(&Object::Num(7f64)));
+
+        let expr = parse("\"a\" + \"b\" == \"ab\"").fold();
+        assert_eq!(expr.constant(), Some(&Object::True));
+
+        let expr = parse("!(1 < 2)").fold();
+        assert_eq!(expr.constant(), Some(&Object::False));
+
+        let expr = create_binary();
+        let value = expr.eval().ok().unwrap();
+        assert_eq!(Box::new(expr).fold().constant(), Some(&value));
+    }
+
+    #[test]
+    fn test_fold_keeps_runtime_error() {
+        let expr = parse("1 + -\"a\"").fold();
+        assert!(expr.constant().is_none());
+        assert!(expr.eval().is_err());
+    }
+
+    #[test]
+    fn test_int_arithmetic() {
+        assert_eq!(parse("2 * 3 + 1").eval().unwrap(), Object::Int(7));
+        assert!(matches!(parse("1 + 0.5").eval().unwrap(), Object::Num(x) if x == 1.5));
+        assert!(matches!(parse("1 / 2").eval().unwrap(), Object::Num(x) if x == 0.5));
+        assert_eq!(parse("-7 ~/ 2").eval().unwrap(), Object::Int(-4));
+        assert_eq!(parse("-7 % 3").eval().unwrap(), Object::Int(2));
+        assert_eq!(parse("9007199254740993 > 9007199254740992").eval().unwrap(), Object::True);
+        assert!(parse("9223372036854775807 + 1").eval().is_err());
+        assert!(parse("1 % 0").eval().is_err());
+        assert_eq!(parse("(-9223372036854775807 - 1) % -1").eval().unwrap(), Object::Int(0));
+        assert!(matches!(parse("5.0 % 0").eval().unwrap(), Object::Num(x) if x.is_nan()));
+        assert!(matches!(parse("5 % 0.0").eval().unwrap(), Object::Num(x) if x.is_nan()));
+        assert!(matches!(parse("5.0 ~/ 0").eval().unwrap(), Object::Num(x) if x == f64::INFINITY));
+        assert!(matches!(parse("5 ~/ 0.0").eval().unwrap(), Object::Num(x) if x == f64::INFINITY));
+        assert!(parse("(-9223372036854775807 - 1) ~/ -1").eval().is_err());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let expr = parse(r#""1 + 1 = ${1 + 1}, ${"nested ${nil}"}!""#);
+        assert_eq!(expr.eval().unwrap(), Object::Str("1 + 1 = 2, nested nil!".to_string()));
+        assert!(parse(r#""${-"a"}""#).fold().eval().is_err());
+    }
+
+    #[test]
+    fn test_power() {
+        assert_eq!(parse("-2 ** 2").eval().unwrap(), Object::Int(-4));
+        assert_eq!(parse("2 ** 3 ** 2").eval().unwrap(), Object::Int(512));
+        assert!(matches!(parse("2 ** -1").eval().unwrap(), Object::Num(x) if x == 0.5));
+        assert!(matches!(parse("4 ** 0.5").eval().unwrap(), Object::Num(x) if x == 2.0));
+        assert_eq!(parse("2 * 3 ** 2 % 5").eval().unwrap(), Object::Int(3));
+        assert!(parse("2 ** 63").eval().is_err());
+        assert!(parse("\"a\" ** 2").eval().is_err());
+    }
+
+    #[test]
+    fn test_bitwise() {
+        assert_eq!(parse("0b1100 & 0b1010 | 1 ^ 3").eval().unwrap(), Object::Int(0b1010));
+        assert_eq!(parse("0xF0 & 0x30 == 0x30").eval().unwrap(), Object::True);
+        assert_eq!(parse("1 << 4 + 1").eval().unwrap(), Object::Int(32));
+        assert_eq!(parse("-16 >> 2").eval().unwrap(), Object::Int(-4));
+        assert_eq!(parse("~5").eval().unwrap(), Object::Int(-6));
+        assert_eq!(parse("6.0 & 3").eval().unwrap(), Object::Int(2));
+        assert!(parse("1.5 | 1").eval().is_err());
+        assert!(parse("1 << 64").eval().is_err());
+        assert!(parse("~\"a\"").eval().is_err());
+    }
+
+    #[test]
+    fn test_conditional_and_comma() {
+        assert_eq!(parse("1 < 2 ? \"yes\" : \"no\"").eval().unwrap(), Object::Str("yes".to_string()));
+        assert_eq!(parse("false ? 1 : nil ? 2 : 3").eval().unwrap(), Object::Int(3));
+        assert_eq!(parse("true ? 1, 2 : 3").eval().unwrap(), Object::Int(2));
+        assert_eq!(parse("true ? 1 : -\"a\"").eval().unwrap(), Object::Int(1));
+        assert_eq!(parse("false ? -\"a\" : 2").fold().constant(), Some(&Object::Int(2)));
+        assert_eq!(parse("(1, 2) + 3").eval().unwrap(), Object::Int(5));
+        assert!(parse("-\"a\", 1").eval().is_err());
+
+        let error = Parser::new(Scanner::new(Rc::from("true ? 1"))).parse().err().unwrap();
+        assert!(error.to_string().contains("Expect ':' after then branch of conditional expression."));
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        assert!(Parser::new(Scanner::new(Rc::from("1 + 2 = 3"))).parse().is_err());
+    }
+
+    #[test]
+    fn test_range_is_non_associative() {
+        assert_eq!(eval("0..2 + 3"), Ok("0..5".to_string()));
+        assert!(Parser::new(Scanner::new(Rc::from("0..1..2"))).parse().is_err());
+    }
 }
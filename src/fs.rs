@@ -0,0 +1,127 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::rc::Rc;
+use crate::error::RuntimeError;
+use crate::native::Native;
+use crate::object::Object;
+use crate::token::Token;
+
+/// 获取fs模块的成员，路径都是字符串，io::Error转换为运行时错误
+pub fn member(name: &str) -> Option<Object> {
+    let native = match name {
+        "read_file" => Native::new("read_file", 1..=1, read_file),
+        "write_file" => Native::new("write_file", 2..=2, write_file),
+        "append_file" => Native::new("append_file", 2..=2, append_file),
+        "exists" => Native::new("exists", 1..=1, exists),
+        "list_dir" => Native::new("list_dir", 1..=1, list_dir),
+        "remove" => Native::new("remove", 1..=1, remove),
+        _ => return None,
+    };
+    Some(Object::Native(Rc::new(native)))
+}
+
+/// 检查参数都是字符串
+fn strings<'a>(token: &Token, arguments: &'a [Object]) -> Result<Vec<&'a str>, RuntimeError> {
+    arguments.iter().map(|argument| match argument {
+        Object::Str(string) => Ok(string.as_str()),
+        _ => Err(RuntimeError::new(token.clone(), "Arguments must be strings.".to_string())),
+    }).collect()
+}
+
+pub fn io_error(token: &Token, action: &str, path: &str, error: io::Error) -> RuntimeError {
+    RuntimeError::new(token.clone(), format!("Can't {} '{}': {}.", action, path, error))
+}
+
+fn read_file(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let path = strings(token, arguments)?[0];
+    fs::read_to_string(path)
+        .map(Object::Str)
+        .map_err(|e| io_error(token, "read", path, e))
+}
+
+/// 写入文件，文件已存在时覆盖
+fn write_file(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let arguments = strings(token, arguments)?;
+    let (path, contents) = (arguments[0], arguments[1]);
+    fs::write(path, contents).map_err(|e| io_error(token, "write", path, e))?;
+    Ok(Object::Nil)
+}
+
+/// 追加到文件末尾，文件不存在时创建
+fn append_file(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let arguments = strings(token, arguments)?;
+    let (path, contents) = (arguments[0], arguments[1]);
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| io_error(token, "append to", path, e))?;
+    Ok(Object::Nil)
+}
+
+fn exists(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let path = strings(token, arguments)?[0];
+    fs::exists(path)
+        .map(Object::new_bool)
+        .map_err(|e| io_error(token, "access", path, e))
+}
+
+/// 按名字排序返回目录下的条目名
+fn list_dir(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let path = strings(token, arguments)?[0];
+    let mut names = fs::read_dir(path)
+        .and_then(|entries| entries
+            .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>())
+        .map_err(|e| io_error(token, "list", path, e))?;
+    names.sort();
+    Ok(Object::new_list(names.into_iter().map(Object::Str).collect()))
+}
+
+/// 删除文件或空目录，符号链接本身按文件删除，不影响其指向的目录
+fn remove(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let path = strings(token, arguments)?[0];
+    let result = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir(path),
+        _ => fs::remove_file(path),
+    };
+    result.map_err(|e| io_error(token, "remove", path, e))?;
+    Ok(Object::Nil)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::tests::eval;
+
+    #[test]
+    fn test_fs() {
+        let dir = std::env::temp_dir().join(format!("lox-fs-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_str().unwrap();
+        let file = format!("{}/a.txt", dir);
+        assert_eq!(eval(&format!("fs.exists(\"{file}\")")), Ok("false".to_string()));
+        assert_eq!(eval(&format!("fs.write_file(\"{file}\", \"hello\")")), Ok("nil".to_string()));
+        assert_eq!(eval(&format!("fs.append_file(\"{file}\", \" world\")")), Ok("nil".to_string()));
+        assert_eq!(eval(&format!("fs.read_file(\"{file}\")")), Ok("\"hello world\"".to_string()));
+        assert_eq!(eval(&format!("fs.list_dir(\"{dir}\")")), Ok("[\"a.txt\"]".to_string()));
+        assert_eq!(eval(&format!("fs.remove(\"{file}\")")), Ok("nil".to_string()));
+        assert!(eval(&format!("fs.read_file(\"{file}\")")).unwrap_err().contains("Can't read"));
+        assert_eq!(eval(&format!("fs.remove(\"{dir}\")")), Ok("nil".to_string()));
+        assert!(eval("fs.read_file(1)").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_symlink_to_dir() {
+        let dir = std::env::temp_dir().join(format!("lox-fs-symlink-test-{}", std::process::id()));
+        let target = dir.join("target");
+        let link = dir.join("link");
+        std::fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        assert_eq!(eval(&format!("fs.remove(\"{}\")", link.to_str().unwrap())), Ok("nil".to_string()));
+        assert!(!link.exists());
+        assert!(target.is_dir());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
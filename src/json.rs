@@ -0,0 +1,423 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::rc::Rc;
+use crate::error::RuntimeError;
+use crate::map::{Map, MapKey};
+use crate::native::Native;
+use crate::object::Object;
+use crate::token::Token;
+
+/// 嵌套数组和对象的最大深度，避免递归时栈溢出
+const MAX_DEPTH: usize = 512;
+
+/// 获取json模块的成员
+pub fn member(name: &str) -> Option<Object> {
+    let native = match name {
+        "parse" => Native::new("parse", 1..=1, parse),
+        "stringify" => Native::new("stringify", 1..=2, stringify),
+        _ => return None,
+    };
+    Some(Object::Native(Rc::new(native)))
+}
+
+/// 将JSON文本解析为object：对象为map，数组为列表，没有小数和指数部分的数为整数
+fn parse(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let source = match &arguments[0] {
+        Object::Str(source) => source,
+        _ => return Err(RuntimeError::new(token.clone(), "Argument must be a string.".to_string())),
+    };
+    let mut parser = JsonParser { token, source, current: 0 };
+    parser.whitespace();
+    let value = parser.value(0)?;
+    parser.whitespace();
+    if parser.current < source.len() {
+        return Err(parser.error("unexpected character after JSON value"));
+    }
+    Ok(value)
+}
+
+struct JsonParser<'a> {
+    token: &'a Token,
+    source: &'a str,
+    current: usize,
+}
+
+impl JsonParser<'_> {
+    fn value(&mut self, depth: usize) -> Result<Object, RuntimeError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => self.string().map(Object::Str),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", Object::True),
+            Some('f') => self.keyword("false", Object::False),
+            Some('n') => self.keyword("null", Object::Nil),
+            Some(_) => Err(self.error("expected value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Object, RuntimeError> {
+        self.advance();
+        let mut entries = Map::new();
+        self.whitespace();
+        if self.try_match('}') {
+            return Ok(Object::new_map(entries));
+        }
+        loop {
+            self.whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected string key"));
+            }
+            let key = self.string()?;
+            self.whitespace();
+            self.expect(':')?;
+            self.whitespace();
+            let value = self.value(depth + 1)?;
+            entries.insert(MapKey::new(self.token, Object::Str(key))?, value);
+            self.whitespace();
+            if self.try_match('}') {
+                return Ok(Object::new_map(entries));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Object, RuntimeError> {
+        self.advance();
+        let mut elements = Vec::new();
+        self.whitespace();
+        if self.try_match(']') {
+            return Ok(Object::new_list(elements));
+        }
+        loop {
+            self.whitespace();
+            elements.push(self.value(depth + 1)?);
+            self.whitespace();
+            if self.try_match(']') {
+                return Ok(Object::new_list(elements));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, RuntimeError> {
+        self.advance();
+        let mut value = String::new();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(value),
+                Some('\\') => value.push(self.escape()?),
+                Some(c) if c < ' ' => {
+                    self.current -= 1;
+                    return Err(self.error("control character in string"));
+                }
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, RuntimeError> {
+        let c = match self.advance() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.hex()?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| self.error("invalid unicode escape"));
+                }
+                // 高代理项后必须跟一个低代理项
+                if !(self.try_match('\\') && self.try_match('u')) {
+                    return Err(self.error("expected low surrogate"));
+                }
+                let low = self.hex()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error("invalid low surrogate"));
+                }
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                return Ok(char::from_u32(code).unwrap());
+            }
+            _ => return Err(self.error("invalid escape")),
+        };
+        Ok(c)
+    }
+
+    fn hex(&mut self) -> Result<u32, RuntimeError> {
+        let digits = self.source.get(self.current..self.current + 4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected 4 hex digits"))?;
+        self.current += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap())
+    }
+
+    fn number(&mut self) -> Result<Object, RuntimeError> {
+        let start = self.current;
+        self.try_match('-');
+        match self.advance() {
+            Some('0') => {}
+            Some('1'..='9') => self.digits(),
+            _ => return Err(self.error("expected digit")),
+        }
+        let mut integer = true;
+        if self.try_match('.') {
+            integer = false;
+            self.expect_digits()?;
+        }
+        if self.try_match('e') || self.try_match('E') {
+            integer = false;
+            if !self.try_match('+') {
+                self.try_match('-');
+            }
+            self.expect_digits()?;
+        }
+        let text = &self.source[start..self.current];
+        match text.parse::<i64>() {
+            Ok(x) if integer => Ok(Object::Int(x)),
+            _ => Ok(Object::Num(text.parse().unwrap())),
+        }
+    }
+
+    fn digits(&mut self) {
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.advance();
+        }
+    }
+
+    fn expect_digits(&mut self) -> Result<(), RuntimeError> {
+        if !matches!(self.peek(), Some('0'..='9')) {
+            return Err(self.error("expected digit"));
+        }
+        self.digits();
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Object) -> Result<Object, RuntimeError> {
+        if !self.source[self.current..].starts_with(keyword) {
+            return Err(self.error("expected value"));
+        }
+        self.current += keyword.len();
+        Ok(value)
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), RuntimeError> {
+        if self.try_match(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", expected)))
+        }
+    }
+
+    fn try_match(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += c.len_utf8();
+        Some(c)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.current..].chars().next()
+    }
+
+    /// 报告当前位置的行号和列号，列号按字符计算
+    fn error(&self, message: &str) -> RuntimeError {
+        let before = &self.source[..self.current];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap().chars().count() + 1;
+        RuntimeError::new(
+            self.token.clone(),
+            format!("Invalid JSON at line {}, column {}: {}.", line, column, message),
+        )
+    }
+}
+
+/// stringify(value, indent)将object转换为JSON文本，indent为每层缩进的空格数，省略或为0时不换行
+fn stringify(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let indent = match arguments.get(1) {
+        None | Some(Object::Nil) => 0,
+        Some(indent) if indent.is_int() && (0..=10).contains(&indent.int()) => indent.int() as usize,
+        Some(_) => {
+            return Err(RuntimeError::new(
+                token.clone(),
+                "Indent must be an integer between 0 and 10.".to_string(),
+            ));
+        }
+    };
+    let mut writer = JsonWriter { token, indent, output: String::new(), path: vec!["$".to_string()], parents: Vec::new() };
+    writer.value(&arguments[0])?;
+    Ok(Object::Str(writer.output))
+}
+
+struct JsonWriter<'a> {
+    token: &'a Token,
+    indent: usize,
+    output: String,
+    /// 当前值在整个结构中的位置，用于报错
+    path: Vec<String>,
+    /// 正在写入的列表和map，用于检测循环引用
+    parents: Vec<*const ()>,
+}
+
+impl JsonWriter<'_> {
+    fn value(&mut self, value: &Object) -> Result<(), RuntimeError> {
+        match value {
+            Object::Nil => self.output.push_str("null"),
+            Object::True => self.output.push_str("true"),
+            Object::False => self.output.push_str("false"),
+            Object::Int(x) => write!(self.output, "{}", x).unwrap(),
+            Object::Num(x) if x.is_finite() => write!(self.output, "{}", x).unwrap(),
+            Object::Num(_) => return Err(self.error("NaN and infinity can't be serialized")),
+            Object::Str(x) => self.string(x),
+            Object::List(elements) => {
+                self.enter(Rc::as_ptr(elements) as *const ())?;
+                let elements = elements.borrow();
+                self.output.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.output.push(',');
+                    }
+                    self.new_line();
+                    self.path.push(format!("[{}]", i));
+                    self.value(element)?;
+                    self.path.pop();
+                }
+                self.leave(!elements.is_empty());
+                self.output.push(']');
+            }
+            Object::Map(entries) => {
+                self.enter(Rc::as_ptr(entries) as *const ())?;
+                let entries = entries.borrow();
+                let mut keys = HashSet::new();
+                self.output.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        self.output.push(',');
+                    }
+                    self.new_line();
+                    self.path.push(format!("[{}]", key));
+                    // 非字符串的键按print的规则转换为字符串，转换后不能与其他键重复
+                    let name = key.object().stringify();
+                    if !keys.insert(name.clone()) {
+                        return Err(self.error(&format!("duplicate key \"{}\"", name)));
+                    }
+                    self.string(&name);
+                    self.output.push_str(if self.indent > 0 { ": " } else { ":" });
+                    self.value(value)?;
+                    self.path.pop();
+                }
+                self.leave(!entries.is_empty());
+                self.output.push('}');
+            }
+            Object::Range(_) | Object::Native(_) | Object::Module(_) => {
+                return Err(self.error(&format!("{} can't be serialized", value)));
+            }
+        }
+        Ok(())
+    }
+
+    fn enter(&mut self, parent: *const ()) -> Result<(), RuntimeError> {
+        if self.parents.contains(&parent) {
+            return Err(self.error("cyclic structure can't be serialized"));
+        }
+        self.parents.push(parent);
+        Ok(())
+    }
+
+    /// 非空的列表或map在结束括号前换行
+    fn leave(&mut self, non_empty: bool) {
+        self.parents.pop();
+        if non_empty {
+            self.new_line();
+        }
+    }
+
+    fn new_line(&mut self) {
+        if self.indent > 0 {
+            self.output.push('\n');
+            self.output.push_str(&" ".repeat(self.indent * self.parents.len()));
+        }
+    }
+
+    fn string(&mut self, string: &str) {
+        self.output.push('"');
+        for c in string.chars() {
+            match c {
+                '"' => self.output.push_str("\\\""),
+                '\\' => self.output.push_str("\\\\"),
+                '\n' => self.output.push_str("\\n"),
+                '\r' => self.output.push_str("\\r"),
+                '\t' => self.output.push_str("\\t"),
+                c if c < ' ' => write!(self.output, "\\u{:04x}", c as u32).unwrap(),
+                c => self.output.push(c),
+            }
+        }
+        self.output.push('"');
+    }
+
+    fn error(&self, message: &str) -> RuntimeError {
+        RuntimeError::new(self.token.clone(), format!("At {}: {}.", self.path.concat(), message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::tests::eval;
+    use crate::object::Object;
+    use crate::token::{Token, TokenType};
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            eval(r#"json.parse("{\"a\": [1, 2.5, -3e2, true, null], \"b\": \"\\u00e9\\ud83d\\ude00\"}")"#),
+            Ok(r#"{"a": [1, 2.5, -300, true, nil], "b": "é😀"}"#.to_string())
+        );
+        assert_eq!(eval(r#"json.parse(" 12345678901234567890 ")"#), Ok("12345678901234567000".to_string()));
+        assert_eq!(eval(r#"json.parse("[1,]")"#), Err("Runtime error: [line 1] Invalid JSON at line 1, column 4: expected value..".to_string()));
+        assert_eq!(eval(r#"json.parse("{\n  \"a\" 1}")"#), Err("Runtime error: [line 1] Invalid JSON at line 2, column 7: expected ':'..".to_string()));
+        assert!(eval(r#"json.parse("\"abc")"#).is_err());
+        assert!(eval(r#"json.parse("01")"#).is_err());
+        assert!(eval(r#"json.parse("")"#).is_err());
+        assert_eq!(eval(r#"json.stringify({"a": [1, 2.5, nil], 1: "x\"y", "e": {}})"#), Ok(r#""{"a":[1,2.5,null],"1":"x\"y","e":{}}""#.to_string()));
+        assert_eq!(eval(r#"json.stringify([1, {"a": []}], 2)"#), Ok("\"[\n  1,\n  {\n    \"a\": []\n  }\n]\"".to_string()));
+        assert_eq!(eval(r#"json.parse(json.stringify({"k": [1, "\u{1}\n"]}, 4)) == {"k": [1, "\u{1}\n"]}"#), Ok("true".to_string()));
+        assert_eq!(eval(r#"json.stringify({"a": [1, math.sqrt]})"#), Err("Runtime error: [line 1] At $[\"a\"][1]: <native fn sqrt> can't be serialized..".to_string()));
+        assert!(eval("json.stringify(math.nan)").is_err());
+        assert_eq!(eval(r#"json.stringify({"m": {1: "a", "1": "b"}})"#), Err("Runtime error: [line 1] At $[\"m\"][\"1\"]: duplicate key \"1\"..".to_string()));
+        assert_eq!(eval(r#"json.stringify([{1: "a"}, {"1": "b"}])"#), Ok(r#""[{"1":"a"},{"1":"b"}]""#.to_string()));
+
+        let list = Object::new_list(vec![]);
+        if let Object::List(elements) = &list {
+            elements.borrow_mut().push(list.clone());
+        }
+        let Some(Object::Native(stringify)) = super::member("stringify") else { panic!() };
+        let paren = Token::new(TokenType::RightParen, ")", None, 1);
+        let error = stringify.call(&paren, std::slice::from_ref(&list)).unwrap_err();
+        assert_eq!(error.to_string(), "Runtime error: [line 1] At $[0]: cyclic structure can't be serialized..");
+        if let Object::List(elements) = &list {
+            elements.borrow_mut().clear();
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::cell::RefCell;
+use crate::error::RuntimeError;
+use crate::native::Native;
+use crate::object::Object;
+use crate::sequence;
+use crate::token::Token;
+
+/// 获取列表的方法
+pub fn method(name: &str) -> Option<Native> {
+    let method = match name {
+        "push" => Native::new("push", 1..=1, push),
+        "pop" => Native::new("pop", 0..=0, pop),
+        "insert" => Native::new("insert", 2..=2, insert),
+        "remove" => Native::new("remove", 1..=1, remove),
+        "len" => Native::new("len", 0..=0, len),
+        "slice" => Native::new("slice", 1..=2, slice),
+        "contains" => Native::new("contains", 1..=1, contains),
+        "reverse" => Native::new("reverse", 0..=0, reverse),
+        "sort" => Native::new("sort", 0..=0, sort),
+        _ => return None,
+    };
+    Some(method)
+}
+
+fn elements(this: &Object) -> &RefCell<Vec<Object>> {
+    match this {
+        Object::List(list) => list,
+        _ => unreachable!("list method bound to {}", this),
+    }
+}
+
+fn push(_: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    elements(this).borrow_mut().push(arguments[0].clone());
+    Ok(Object::Nil)
+}
+
+fn pop(token: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    elements(this).borrow_mut().pop().ok_or_else(|| {
+        RuntimeError::new(token.clone(), "Can't pop from an empty list.".to_string())
+    })
+}
+
+fn insert(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let mut list = elements(this).borrow_mut();
+    // 插入位置可以等于len，即插入到末尾
+    let i = match sequence::position(token, "List", list.len(), &arguments[0])? {
+        Some(i) if i <= list.len() => i,
+        _ => return Err(sequence::out_of_range(token, "List", list.len(), &arguments[0])),
+    };
+    list.insert(i, arguments[1].clone());
+    Ok(Object::Nil)
+}
+
+fn remove(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let mut list = elements(this).borrow_mut();
+    let i = sequence::index(token, "List", list.len(), &arguments[0])?;
+    Ok(list.remove(i))
+}
+
+fn len(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(elements(this).borrow().len() as i64))
+}
+
+/// slice(start, end)返回[start, end)之间元素组成的新列表，省略end时到列表末尾，超出范围的下标会被截断
+fn slice(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let list = elements(this).borrow();
+    let range = sequence::slice(token, "List", list.len(), arguments.first(), arguments.get(1))?;
+    Ok(Object::new_list(list[range].to_vec()))
+}
+
+fn contains(_: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::new_bool(elements(this).borrow().contains(&arguments[0])))
+}
+
+fn reverse(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    elements(this).borrow_mut().reverse();
+    Ok(Object::Nil)
+}
+
+/// 原地排序，元素必须都是数值或都是字符串
+fn sort(token: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    let mut list = elements(this).borrow_mut();
+    if list.iter().all(|x| x.is_num()) {
+        list.sort_by(|x, y| x.compare(y).unwrap_or_else(|| x.num().total_cmp(&y.num())));
+    } else if list.iter().all(|x| x.is_str()) {
+        list.sort_by(|x, y| x.str().cmp(y.str()));
+    } else {
+        return Err(RuntimeError::new(
+            token.clone(),
+            "Can only sort lists of numbers or of strings.".to_string(),
+        ));
+    }
+    Ok(Object::Nil)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::tests::eval;
+    use crate::object::Object;
+    use crate::token::{Token, TokenType};
+
+    #[test]
+    fn test_list() {
+        assert_eq!(eval("[1, \"a\", [nil],]"), Ok("[1, \"a\", [nil]]".to_string()));
+        assert_eq!(eval("[1, 2, 3][-1] + [1, 2, 3][0]"), Ok("4".to_string()));
+        assert_eq!(eval("[1, 2, 3][1] = 5"), Ok("5".to_string()));
+        assert_eq!(eval("[3, 1, 2].len()"), Ok("3".to_string()));
+        assert_eq!(eval("[3, 1, 2].slice(1)"), Ok("[1, 2]".to_string()));
+        assert_eq!(eval("[3, 1, 2].slice(-2, 10)"), Ok("[1, 2]".to_string()));
+        assert_eq!(eval("[3, 1, 2].contains(1.0)"), Ok("true".to_string()));
+        assert_eq!(eval("[3, 1, 2].remove(-1)"), Ok("2".to_string()));
+        assert_eq!(eval("[].pop()"), Err("Runtime error: [line 1] Can't pop from an empty list..".to_string()));
+        assert_eq!(eval("[1, 2, 3][3]"), Err("Runtime error: [line 1] List index 3 out of range for length 3..".to_string()));
+        assert!(eval("[1, 2, 3][1.5]").is_err());
+        assert!(eval("[1, 2].push()").is_err());
+        assert!(eval("[1, \"a\"].sort()").is_err());
+        assert!(eval("[1].size()").is_err());
+        assert!(eval("1[0]").is_err());
+    }
+
+    #[test]
+    fn test_list_methods_mutate_in_place() {
+        let list = Object::new_list(vec![Object::Int(3), Object::Num(1.5), Object::Int(2)]);
+        let call = |name: &str, arguments: &[Object]| {
+            let paren = Token::new(TokenType::RightParen, ")", None, 1);
+            super::method(name).unwrap().bind(list.clone()).call(&paren, arguments).unwrap()
+        };
+        call("push", &[Object::Int(0)]);
+        call("insert", &[Object::Int(1), Object::Str("x".to_string())]);
+        assert_eq!(list.to_string(), "[3, \"x\", 1.5, 2, 0]");
+        call("remove", &[Object::Int(1)]);
+        call("sort", &[]);
+        assert_eq!(list.to_string(), "[0, 1.5, 2, 3]");
+        call("reverse", &[]);
+        assert_eq!(call("pop", &[]), Object::Int(0));
+        assert_eq!(list.to_string(), "[3, 2, 1.5]");
+    }
+}
@@ -4,24 +4,35 @@ mod token;
 mod expr;
 mod parser;
 mod object;
+mod native;
+mod list;
+mod sequence;
+mod map;
+mod range;
+mod module;
+mod math;
+mod string;
+mod fs;
+mod stdio;
+mod json;
 
 use crate::error::{LoxError, SyntaxError};
 use crate::scanner::Scanner;
 use std::env::args;
 use std::fs::File;
+use std::rc::Rc;
 use std::io::{self, stdout, BufRead, BufReader, Read, Write};
 use crate::LoxError::{Runtime, Syntax};
 use crate::parser::Parser;
 
 pub fn main() {
     let args: Vec<String> = args().collect();
-    println!("args: {:?}", args);
     match args.len() {
         1 => run_prompt(),
-        2 => run_file(&args[1]).expect("Could not run file"),
         _ => {
-            println!("Usage: lox-rust [script]");
-            std::process::exit(64);
+            // 脚本路径之后的参数交给脚本，通过io.args()获取
+            stdio::set_args(args[2..].to_vec());
+            run_file(&args[1]).expect("Could not run file")
         }
     }
 }
@@ -67,16 +78,11 @@ fn run_prompt() {
 }
 
 fn run(source: &[u8]) -> Result<(), LoxError> {
-    let source = String::from_utf8(source.to_vec())
-        .expect("from utf8 error")
-        .chars()
-        .collect::<Vec<char>>();
+    // 解码后的源码只在这里复制一次到共享的缓冲区，之后所有token都引用它
+    let source = Rc::from(Scanner::decode(source).map_err(Syntax)?);
 
-    let mut scanner = Scanner::new(source);
-    let tokens = scanner.scan_tokens().map_err(Syntax)?;
-
-    let mut parser = Parser::new(tokens.clone());
-    let expr = parser.parse().map_err(Syntax)?;
+    let mut parser = Parser::new(Scanner::new(source));
+    let expr = parser.parse().map_err(Syntax)?.fold();
     let result = expr.eval().map_err(Runtime)?;
     println!("{}", result);
     Ok(())
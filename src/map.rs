@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::fmt::{self, Formatter};
+use std::hash::{Hash, Hasher};
+use indexmap::IndexMap;
+use crate::error::RuntimeError;
+use crate::native::Native;
+use crate::object::Object;
+use crate::token::Token;
+
+/// 按插入顺序保存的map
+pub type Map = IndexMap<MapKey, Object>;
+
+/// map的键，只能是字符串、数值（NaN除外）、bool和nil
+///
+/// 相等的键必须有相同的hash：整数值的浮点数与对应的整数相等，因此按整数计算hash
+#[derive(Debug, Clone)]
+pub struct MapKey(Object);
+
+impl MapKey {
+    pub fn new(token: &Token, key: Object) -> Result<Self, RuntimeError> {
+        match key {
+            Object::Num(x) if x.is_nan() => Err(RuntimeError::new(
+                token.clone(),
+                "Map key can't be NaN.".to_string(),
+            )),
+            Object::Int(_) | Object::Num(_) | Object::Str(_) | Object::Nil | Object::True | Object::False => {
+                Ok(MapKey(key))
+            }
+            _ => Err(RuntimeError::new(
+                token.clone(),
+                "Map key must be a string, number, boolean or nil.".to_string(),
+            )),
+        }
+    }
+
+    pub fn object(&self) -> &Object {
+        &self.0
+    }
+}
+
+impl PartialEq for MapKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for MapKey {}
+
+impl Hash for MapKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.0 {
+            key if key.is_int() => {
+                0u8.hash(state);
+                key.int().hash(state);
+            }
+            Object::Num(x) => {
+                1u8.hash(state);
+                x.to_bits().hash(state);
+            }
+            Object::Str(x) => {
+                2u8.hash(state);
+                x.hash(state);
+            }
+            Object::True => 3u8.hash(state),
+            Object::False => 4u8.hash(state),
+            _ => 5u8.hash(state),
+        }
+    }
+}
+
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// 获取map的方法
+pub fn method(name: &str) -> Option<Native> {
+    let method = match name {
+        "keys" => Native::new("keys", 0..=0, keys),
+        "values" => Native::new("values", 0..=0, values),
+        "entries" => Native::new("entries", 0..=0, entries),
+        "has" => Native::new("has", 1..=1, has),
+        "remove" => Native::new("remove", 1..=1, remove),
+        "len" => Native::new("len", 0..=0, len),
+        _ => return None,
+    };
+    Some(method)
+}
+
+/// 获取key对应的值，key不存在时报错
+pub fn get(token: &Token, map: &Map, key: Object) -> Result<Object, RuntimeError> {
+    let key = MapKey::new(token, key)?;
+    map.get(&key).cloned().ok_or_else(|| {
+        RuntimeError::new(token.clone(), format!("Undefined key {}.", key))
+    })
+}
+
+fn entries_of(this: &Object) -> &RefCell<Map> {
+    match this {
+        Object::Map(map) => map,
+        _ => unreachable!("map method bound to {}", this),
+    }
+}
+
+fn keys(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    let keys = entries_of(this).borrow().keys().map(|k| k.object().clone()).collect();
+    Ok(Object::new_list(keys))
+}
+
+fn values(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    let values = entries_of(this).borrow().values().cloned().collect();
+    Ok(Object::new_list(values))
+}
+
+/// 以[key, value]列表的形式按插入顺序返回所有键值对
+fn entries(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    let entries = entries_of(this)
+        .borrow()
+        .iter()
+        .map(|(k, v)| Object::new_list(vec![k.object().clone(), v.clone()]))
+        .collect();
+    Ok(Object::new_list(entries))
+}
+
+fn has(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let key = MapKey::new(token, arguments[0].clone())?;
+    Ok(Object::new_bool(entries_of(this).borrow().contains_key(&key)))
+}
+
+/// 删除key并返回对应的值，key不存在时返回nil
+fn remove(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let key = MapKey::new(token, arguments[0].clone())?;
+    Ok(entries_of(this).borrow_mut().shift_remove(&key).unwrap_or(Object::Nil))
+}
+
+fn len(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(entries_of(this).borrow().len() as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::expr::tests::eval;
+    use crate::map::MapKey;
+    use crate::object::Object;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+    use crate::token::{Token, TokenType};
+
+    #[test]
+    fn test_map() {
+        assert_eq!(eval("{\"a\": 1, 2: [], true: nil, nil: 0,}"), Ok("{\"a\": 1, 2: [], true: nil, nil: 0}".to_string()));
+        assert_eq!(eval("{}"), Ok("{}".to_string()));
+        assert_eq!(eval("{1: \"int\"}[1.0]"), Ok("\"int\"".to_string()));
+        assert_eq!(eval("{\"b\": 1, \"a\": 2}.keys()"), Ok("[\"b\", \"a\"]".to_string()));
+        assert_eq!(eval("{\"b\": 1, \"a\": 2}.values()"), Ok("[1, 2]".to_string()));
+        assert_eq!(eval("{\"b\": 1, \"a\": 2}.entries()"), Ok("[[\"b\", 1], [\"a\", 2]]".to_string()));
+        assert_eq!(eval("{\"a\": 1}.has(\"a\")"), Ok("true".to_string()));
+        assert_eq!(eval("{\"a\": 1}.remove(\"a\")"), Ok("1".to_string()));
+        assert_eq!(eval("{\"a\": 1}[\"b\"] = 2"), Ok("2".to_string()));
+        assert_eq!(eval("{\"a\": 1} == {\"a\": 1.0}"), Ok("true".to_string()));
+        assert_eq!(eval("{\"a\": 1}[\"b\"]"), Err("Runtime error: [line 1] Undefined key \"b\"..".to_string()));
+        assert!(eval("{0 / 0: 1}").is_err());
+        assert!(eval("{[]: 1}").is_err());
+        assert!(Parser::new(Scanner::new(Rc::from("{1 2}"))).parse().is_err());
+    }
+
+    #[test]
+    fn test_map_key_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let token = Token::new(TokenType::LeftBrace, "{", None, 1);
+        let hash = |key: Object| {
+            let mut hasher = DefaultHasher::new();
+            MapKey::new(&token, key).unwrap().hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(Object::Int(1)), hash(Object::Num(1.0)));
+        assert_eq!(hash(Object::Num(0.0)), hash(Object::Num(-0.0)));
+        assert_ne!(hash(Object::Int(1)), hash(Object::Str("1".to_string())));
+        assert!(MapKey::new(&token, Object::Num(f64::NAN)).is_err());
+
+        assert_eq!(eval("{9223372036854775807: 1, 9223372036854775808.0: 2}.len()"), Ok("2".to_string()));
+        assert_eq!(eval("{9223372036854775807: 1}.has(9223372036854775808.0)"), Ok("false".to_string()));
+        assert_eq!(eval("{1: 0, 2: 0, 9223372036854775807: 1}.has(9223372036854775808.0)"), Ok("false".to_string()));
+        assert_eq!(eval("9223372036854775807 < 9223372036854775808.0"), Ok("true".to_string()));
+    }
+}
@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+use crate::error::{check_number_arguments, RuntimeError};
+use crate::native::Native;
+use crate::object::Object;
+use crate::token::Token;
+
+/// 获取math模块的成员
+pub fn member(name: &str) -> Option<Object> {
+    let native = match name {
+        "pi" => return Some(Object::Num(std::f64::consts::PI)),
+        "e" => return Some(Object::Num(std::f64::consts::E)),
+        "inf" => return Some(Object::Num(f64::INFINITY)),
+        "nan" => return Some(Object::Num(f64::NAN)),
+        "sqrt" => Native::new("sqrt", 1..=1, sqrt),
+        "pow" => Native::new("pow", 2..=2, pow),
+        "abs" => Native::new("abs", 1..=1, abs),
+        "floor" => Native::new("floor", 1..=1, floor),
+        "ceil" => Native::new("ceil", 1..=1, ceil),
+        "round" => Native::new("round", 1..=1, round),
+        "min" => Native::new("min", 1..=usize::MAX, min),
+        "max" => Native::new("max", 1..=usize::MAX, max),
+        "sin" => Native::new("sin", 1..=1, sin),
+        "cos" => Native::new("cos", 1..=1, cos),
+        "tan" => Native::new("tan", 1..=1, tan),
+        "log" => Native::new("log", 1..=1, log),
+        "exp" => Native::new("exp", 1..=1, exp),
+        "is_nan" => Native::new("is_nan", 1..=1, is_nan),
+        _ => return None,
+    };
+    Some(Object::Native(Rc::new(native)))
+}
+
+/// 检查参数后对浮点数调用f
+fn float(token: &Token, arguments: &[Object], f: fn(f64) -> f64) -> Result<Object, RuntimeError> {
+    check_number_arguments(token, arguments)?;
+    Ok(Object::Num(f(arguments[0].num())))
+}
+
+/// 取整函数，整数原样返回
+fn integral(token: &Token, arguments: &[Object], f: fn(f64) -> f64) -> Result<Object, RuntimeError> {
+    check_number_arguments(token, arguments)?;
+    match &arguments[0] {
+        Object::Int(x) => Ok(Object::Int(*x)),
+        x => Ok(Object::Num(f(x.num()))),
+    }
+}
+
+fn sqrt(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    float(token, arguments, f64::sqrt)
+}
+
+/// 与**相同：两个整数且指数非负时结果为整数
+fn pow(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    check_number_arguments(token, arguments)?;
+    match (&arguments[0], &arguments[1]) {
+        (Object::Int(x), Object::Int(y)) if *y >= 0 => u32::try_from(*y)
+            .ok()
+            .and_then(|y| x.checked_pow(y))
+            .map(Object::Int)
+            .ok_or_else(|| RuntimeError::new(token.clone(), "Integer overflow.".to_string())),
+        (x, y) => Ok(Object::Num(x.num().powf(y.num()))),
+    }
+}
+
+fn abs(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    check_number_arguments(token, arguments)?;
+    match &arguments[0] {
+        Object::Int(x) => x
+            .checked_abs()
+            .map(Object::Int)
+            .ok_or_else(|| RuntimeError::new(token.clone(), "Integer overflow.".to_string())),
+        x => Ok(Object::Num(x.num().abs())),
+    }
+}
+
+fn floor(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    integral(token, arguments, f64::floor)
+}
+
+fn ceil(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    integral(token, arguments, f64::ceil)
+}
+
+/// 四舍五入，.5向远离0的方向取整
+fn round(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    integral(token, arguments, f64::round)
+}
+
+/// 返回参数中最小的数，有NaN时返回NaN
+fn min(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    extremum(token, arguments, Ordering::Less)
+}
+
+/// 返回参数中最大的数，有NaN时返回NaN
+fn max(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    extremum(token, arguments, Ordering::Greater)
+}
+
+fn extremum(token: &Token, arguments: &[Object], wanted: Ordering) -> Result<Object, RuntimeError> {
+    check_number_arguments(token, arguments)?;
+    let mut result = &arguments[0];
+    for x in &arguments[1..] {
+        match x.compare(result) {
+            Some(ordering) if ordering == wanted => result = x,
+            Some(_) => {}
+            None => return Ok(Object::Num(f64::NAN)),
+        }
+    }
+    Ok(result.clone())
+}
+
+fn sin(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    float(token, arguments, f64::sin)
+}
+
+fn cos(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    float(token, arguments, f64::cos)
+}
+
+fn tan(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    float(token, arguments, f64::tan)
+}
+
+/// 自然对数
+fn log(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    float(token, arguments, f64::ln)
+}
+
+fn exp(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    float(token, arguments, f64::exp)
+}
+
+fn is_nan(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    check_number_arguments(token, arguments)?;
+    Ok(Object::new_bool(arguments[0].num().is_nan()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::tests::eval;
+
+    #[test]
+    fn test_math() {
+        assert_eq!(eval("math.sqrt(16)"), Ok("4".to_string()));
+        assert_eq!(eval("math.pow(2, 10)"), Ok("1024".to_string()));
+        assert_eq!(eval("math.pow(2, 0.5) == math.sqrt(2)"), Ok("true".to_string()));
+        assert_eq!(eval("math.abs(-3)"), Ok("3".to_string()));
+        assert_eq!(eval("[math.floor(-2.5), math.ceil(2.1), math.round(2.5), math.round(7)]"), Ok("[-3, 3, 3, 7]".to_string()));
+        assert_eq!(eval("[math.min(3, 1.5, 2), math.max(3, 1.5, 2), math.max(1)]"), Ok("[1.5, 3, 1]".to_string()));
+        assert_eq!(eval("math.is_nan(math.min(1, math.nan))"), Ok("true".to_string()));
+        assert_eq!(eval("[math.sin(0), math.cos(0), math.log(math.e), math.exp(0)]"), Ok("[0, 1, 1, 1]".to_string()));
+        assert_eq!(eval("math.tan(math.pi / 4) > 0.99"), Ok("true".to_string()));
+        assert_eq!(eval("[-math.inf < 0, math.inf > 0]"), Ok("[true, true]".to_string()));
+        assert_eq!(eval("math.sqrt(\"4\")"), Err("Runtime error: [line 1] Arguments must be numbers..".to_string()));
+        assert_eq!(eval("math.min()"), Err("Runtime error: [line 1] Expected at least 1 arguments but got 0..".to_string()));
+        assert!(eval("math.abs(-9223372036854775807 - 1)").is_err());
+        assert_eq!(eval("math.tau"), Err("Runtime error: [line 1] Undefined property 'tau'..".to_string()));
+        assert_eq!(eval("foo"), Err("Runtime error: [line 1] Undefined variable 'foo'..".to_string()));
+        assert_eq!(eval("math"), Ok("<module math>".to_string()));
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt::{self, Formatter};
+use crate::{fs, json, math, stdio};
+use crate::string;
+use crate::object::Object;
+
+/// 内置模块，成员在访问时才创建
+#[derive(Debug)]
+pub struct Module {
+    name: &'static str,
+    member: fn(&str) -> Option<Object>,
+}
+
+impl Module {
+    /// 获取模块的成员
+    pub fn member(&self, name: &str) -> Option<Object> {
+        (self.member)(name)
+    }
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
+
+static MATH: Module = Module { name: "math", member: math::member };
+static STRING: Module = Module { name: "string", member: string::member };
+static FS: Module = Module { name: "fs", member: fs::member };
+static IO: Module = Module { name: "io", member: stdio::member };
+static JSON: Module = Module { name: "json", member: json::member };
+
+/// 查找全局名字，目前只有内置模块
+pub fn global(name: &str) -> Option<Object> {
+    let module = match name {
+        "math" => &MATH,
+        "string" => &STRING,
+        "fs" => &FS,
+        "io" => &IO,
+        "json" => &JSON,
+        _ => return None,
+    };
+    Some(Object::Module(module))
+}
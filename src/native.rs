@@ -0,0 +1,58 @@
+use std::fmt::{self, Formatter};
+use std::ops::RangeInclusive;
+use crate::error::RuntimeError;
+use crate::object::Object;
+use crate::token::Token;
+
+/// 内置函数的实现，参数依次为调用处的token、方法绑定的对象和调用参数
+pub type NativeFn = fn(&Token, &Object, &[Object]) -> Result<Object, RuntimeError>;
+
+/// 用Rust实现的内置函数或方法
+#[derive(Debug)]
+pub struct Native {
+    name: &'static str,
+    arity: RangeInclusive<usize>,
+    receiver: Object,
+    function: NativeFn,
+}
+
+impl Native {
+    pub fn new(name: &'static str, arity: RangeInclusive<usize>, function: NativeFn) -> Self {
+        Native {
+            name,
+            arity,
+            receiver: Object::Nil,
+            function,
+        }
+    }
+
+    /// 将方法绑定到调用它的对象上
+    pub fn bind(self, receiver: Object) -> Self {
+        Native { receiver, ..self }
+    }
+
+    /// 检查参数个数后调用函数，paren为调用处的')'
+    pub fn call(&self, paren: &Token, arguments: &[Object]) -> Result<Object, RuntimeError> {
+        if !self.arity.contains(&arguments.len()) {
+            let (min, max) = (self.arity.start(), self.arity.end());
+            let expected = if min == max {
+                min.to_string()
+            } else if *max == usize::MAX {
+                format!("at least {}", min)
+            } else {
+                format!("{} to {}", min, max)
+            };
+            return Err(RuntimeError::new(
+                paren.clone(),
+                format!("Expected {} arguments but got {}.", expected, arguments.len()),
+            ));
+        }
+        (self.function)(paren, &self.receiver, arguments)
+    }
+}
+
+impl fmt::Display for Native {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
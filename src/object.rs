@@ -1,10 +1,23 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::fmt::{self, Formatter};
+use std::rc::Rc;
+use crate::map::Map;
+use crate::module::Module;
+use crate::native::Native;
+use crate::range::Range;
 use crate::object::Object::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Object {
+    Int(i64),
     Num(f64),
     Str(String),
+    List(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<Map>>),
+    Range(Rc<Range>),
+    Module(&'static Module),
+    Native(Rc<Native>),
     Nil,
     True,
     False,
@@ -13,8 +26,20 @@ pub enum Object {
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            Int(x) => write!(f, "{x}"),
             Num(x) => write!(f, "{x}"),
             Str(x) => write!(f, "\"{x}\""),
+            List(x) => {
+                let elements = x.borrow().iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Map(x) => {
+                let entries = x.borrow().iter().map(|(k, v)| format!("{k}: {v}")).collect::<Vec<_>>();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Range(x) => write!(f, "{x}"),
+            Module(x) => write!(f, "{x}"),
+            Native(x) => write!(f, "{x}"),
             Nil => write!(f, "nil"),
             True => write!(f, "true"),
             False => write!(f, "false"),
@@ -23,22 +48,49 @@ impl fmt::Display for Object {
 }
 
 impl Object {
-    /// 获取数值类型的object的值
+    /// 获取数值类型的object的值，整数会转换为浮点数
     pub fn num(&self) -> f64 {
         match self {
+            Int(x) => *x as f64,
             Num(x) => *x,
             _ => 0f64
         }
     }
 
-    /// 检查object是否为数值类型
+    /// 检查object是否为数值类型（整数或浮点数）
     pub fn is_num(&self) -> bool {
+        matches!(self, Int(_) | Num(_))
+    }
+
+    /// 获取整数值类型的object的值
+    pub fn int(&self) -> i64 {
+        match self {
+            Int(x) => *x,
+            Num(x) if self.is_int() => *x as i64,
+            _ => 0
+        }
+    }
+
+    /// 检查object是否为整数值，小数部分为0且在i64范围内的浮点数也是整数值
+    pub fn is_int(&self) -> bool {
         match self {
-            Num(_) => true,
+            Int(_) => true,
+            Num(x) => x.fract() == 0f64 && *x >= i64::MIN as f64 && *x < i64::MAX as f64,
             _ => false
         }
     }
 
+    /// 比较两个数值类型的object，两者都是整数值时精确比较，只有一个是整数值时也不会因转换为浮点数而失去精度
+    pub fn compare(&self, other: &Object) -> Option<Ordering> {
+        match (self, other) {
+            _ if self.is_int() && other.is_int() => Some(self.int().cmp(&other.int())),
+            _ if self.is_int() && other.is_num() => compare_int_num(self.int(), other.num()),
+            _ if self.is_num() && other.is_int() => compare_int_num(other.int(), self.num()).map(Ordering::reverse),
+            _ if self.is_num() && other.is_num() => self.num().partial_cmp(&other.num()),
+            _ => None
+        }
+    }
+
     /// 获取字符串类型的object的值
     pub fn str(&self) -> &str {
         match self {
@@ -49,49 +101,74 @@ impl Object {
 
     /// 检查object是否为字符串类型
     pub fn is_str(&self) -> bool {
+        matches!(self, Str(_))
+    }
+
+    /// 按print的规则将object转换为字符串，字符串不带引号
+    pub fn stringify(&self) -> String {
         match self {
-            Str(_) => true,
-            _ => false
+            Str(x) => x.clone(),
+            _ => self.to_string()
         }
     }
 
     /// 检查object是否为true
     pub fn is_true(&self) -> bool {
-        match self {
-            Nil => false,
-            False => false,
-            _ => true
-        }
+        !matches!(self, Nil | False)
+    }
+
+    /// 创建一个列表类型的object
+    pub fn new_list(elements: Vec<Object>) -> Object {
+        List(Rc::new(RefCell::new(elements)))
+    }
+
+    /// 创建一个map类型的object
+    pub fn new_map(entries: Map) -> Object {
+        Map(Rc::new(RefCell::new(entries)))
     }
 
     /// 创建一个bool类型的object
     pub fn new_bool(x :bool) -> Object {
         if x { True } else { False }
     }
+}
 
-    // 判断是否与另一个object相等 @PartialEq
-    // fn eq(&self, other: &Self) -> bool {
-    //     match self {
-    //         Num(x) => {
-    //             if let Num(y) = other { return x == y; }
-    //             false
-    //         },
-    //         Str(x) => {
-    //             if let Str(y) = other { return x == y; }
-    //             false
-    //         },
-    //         True => if let True = other { true } else { false },
-    //         False => if let False = other { true } else { false },
-    //         Nil => if let Nil = other { true } else { false },
-    //     }
-    // }
-
+/// 比较整数和非整数值的浮点数
+///
+/// 超出i64范围的浮点数（包括无穷）一定更大或更小；其余的浮点数有小数部分，绝对值小于2^52，
+/// 整数转换为浮点数时即使有舍入也不会越过它，因此可以按浮点数比较
+fn compare_int_num(x: i64, y: f64) -> Option<Ordering> {
+    const LIMIT: f64 = 9223372036854775808f64;
+    if y >= LIMIT {
+        Some(Ordering::Less)
+    } else if y < -LIMIT {
+        Some(Ordering::Greater)
+    } else {
+        (x as f64).partial_cmp(&y)
+    }
+}
 
+impl PartialEq for Object {
+    /// 判断是否与另一个object相等，整数与浮点数按数值比较
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Int(_) | Num(_), Int(_) | Num(_)) => self.compare(other) == Some(Ordering::Equal),
+            (Str(x), Str(y)) => x == y,
+            (List(x), List(y)) => x == y,
+            (Map(x), Map(y)) => x == y,
+            (Range(x), Range(y)) => x == y,
+            (Module(x), Module(y)) => x == y,
+            (Native(x), Native(y)) => Rc::ptr_eq(x, y),
+            (Nil, Nil) | (True, True) | (False, False) => true,
+            _ => false
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::object::Object::{Num, Str};
+    use std::cmp::Ordering;
+    use crate::object::Object::{Int, Num, Str};
 
     #[test]
     fn test_equal() {
@@ -125,4 +202,28 @@ mod tests {
         assert_ne!(x, y);
         println!("{} {}", x, y);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_int_equal() {
+        assert_eq!(Int(1), Int(1));
+        assert_eq!(Int(1), Num(1f64));
+        assert_ne!(Int(1), Num(1.5));
+        assert_ne!(Int(1), Str("1".to_string()));
+        assert_ne!(Num(f64::NAN), Num(f64::NAN));
+        assert_ne!(Int(9007199254740993), Num(9007199254740992f64));
+    }
+
+    #[test]
+    fn test_compare_at_i64_boundary() {
+        let two_63 = Num(9223372036854775808f64);
+        assert_ne!(Int(i64::MAX), two_63);
+        assert_ne!(Int(i64::MAX - 1), two_63);
+        assert_eq!(Int(i64::MAX).compare(&two_63), Some(Ordering::Less));
+        assert_eq!(two_63.compare(&Int(i64::MAX)), Some(Ordering::Greater));
+        assert_eq!(Int(i64::MIN), Num(-9223372036854775808f64));
+        assert_eq!(Int(i64::MIN).compare(&Num(-1e19)), Some(Ordering::Greater));
+        assert_eq!(Int(i64::MIN).compare(&Num(f64::NEG_INFINITY)), Some(Ordering::Greater));
+        assert_eq!(Int(1).compare(&Num(1.5)), Some(Ordering::Less));
+        assert_eq!(Int(1).compare(&Num(f64::NAN)), None);
+    }
+}
@@ -5,80 +5,247 @@ use crate::SyntaxError;
 use crate::object::Object;
 use crate::token::TokenType::*;
 
-pub struct Parser {
-    tokens: Vec<Token>,
-    current: usize,
+/// 语法分析器，从tokens中按需读取token
+pub struct Parser<I: Iterator<Item = Result<Token, SyntaxError>>> {
+    tokens: I,
+    current: Option<Token>,
+    previous: Option<Token>,
+    /// 读取token时遇到的第一个词法错误
+    error: Option<SyntaxError>,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+impl<I: Iterator<Item = Result<Token, SyntaxError>>> Parser<I> {
+    pub fn new(tokens: I) -> Self {
+        let mut parser = Parser { tokens, current: None, previous: None, error: None };
+        parser.current = Some(parser.next_token());
+        parser
     }
 
     pub fn parse(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
-        return self.expression();
+        let result = self.expression().and_then(|expr| {
+            self.consume(&Eof, "Expect end of expression.")?;
+            Ok(expr)
+        });
+        // 词法错误出现在它所导致的语法错误之前，优先报告
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => result,
+        }
     }
 
     fn expression(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
-        return self.equality();
+        self.comma()
+    }
+
+    /// 逗号运算符依次求值两侧，结果为右侧的值
+    fn comma(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let mut expr = self.assignment()?;
+
+        while self.try_match(&[Comma]) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.assignment()?;
+            expr = BinaryExpr::new(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    /// 赋值右结合，左侧必须是可以被赋值的表达式
+    fn assignment(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let expr = self.conditional()?;
+
+        if self.try_match(&[Equal]) {
+            let equals = self.previous().unwrap().clone();
+            let value = self.assignment()?;
+            return expr.assign(value).ok_or_else(|| {
+                SyntaxError::new(equals.line, "Invalid assignment target.".to_string())
+            });
+        }
+
+        Ok(expr)
+    }
+
+    /// 条件运算符右结合：a ? b : c ? d : e 即 a ? b : (c ? d : e)
+    fn conditional(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let expr = self.equality()?;
+
+        if self.try_match(&[Question]) {
+            let then_branch = self.expression()?;
+            self.consume(&Colon, "Expect ':' after then branch of conditional expression.")?;
+            let else_branch = self.conditional()?;
+            return Ok(ConditionalExpr::new(expr, then_branch, else_branch));
+        }
+
+        Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
-        let left = self.comparison()?;
+        let mut expr = self.bit_or()?;
 
         while self.try_match(&[BangEqual, EqualEqual]) {
             let operator = self.previous().unwrap().clone();
-            let right= self.comparison()?;
-            return Ok(BinaryExpr::new(left, operator, right));
+            let right = self.bit_or()?;
+            expr = BinaryExpr::new(expr, operator, right);
         }
 
-        Ok(left)
+        Ok(expr)
+    }
+
+    /// 位运算的优先级介于相等与比较之间，a & b == c 即 (a & b) == c
+    fn bit_or(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let mut expr = self.bit_xor()?;
+
+        while self.try_match(&[Pipe]) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.bit_xor()?;
+            expr = BinaryExpr::new(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_xor(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let mut expr = self.bit_and()?;
+
+        while self.try_match(&[Caret]) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.bit_and()?;
+            expr = BinaryExpr::new(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let mut expr = self.comparison()?;
+
+        while self.try_match(&[Ampersand]) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.comparison()?;
+            expr = BinaryExpr::new(expr, operator, right);
+        }
+
+        Ok(expr)
     }
 
     fn comparison(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
-        let left = self.term()?;
+        let mut expr = self.range()?;
 
         while self.try_match(&[Greater, GreaterEqual, Less, LessEqual]) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.range()?;
+            expr = BinaryExpr::new(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    /// 范围不能连写，0..n+1 即 0..(n+1)
+    fn range(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let expr = self.shift()?;
+
+        if self.try_match(&[DotDot, DotDotEqual]) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.shift()?;
+            return Ok(BinaryExpr::new(expr, operator, right));
+        }
+
+        Ok(expr)
+    }
+
+    fn shift(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let mut expr = self.term()?;
+
+        while self.try_match(&[LessLess, GreaterGreater]) {
             let operator = self.previous().unwrap().clone();
             let right = self.term()?;
-            return Ok(BinaryExpr::new(left, operator, right));
+            expr = BinaryExpr::new(expr, operator, right);
         }
 
-        Ok(left)
+        Ok(expr)
     }
 
     fn term(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
-        let left = self.factor()?;
+        let mut expr = self.factor()?;
 
         while self.try_match(&[Minus, Plus]) {
             let operator = self.previous().unwrap().clone();
             let right = self.factor()?;
-            return Ok(BinaryExpr::new(left, operator, right));
+            expr = BinaryExpr::new(expr, operator, right);
         }
 
-        Ok(left)
+        Ok(expr)
     }
 
     fn factor(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
-        let left = self.unary()?;
+        let mut expr = self.unary()?;
 
-        while self.try_match(&[Slash, Star]) {
+        while self.try_match(&[Slash, Star, Percent, TildeSlash]) {
             let operator = self.previous().unwrap().clone();
             let right = self.unary()?;
-            return Ok(BinaryExpr::new(left, operator, right));
+            expr = BinaryExpr::new(expr, operator, right);
         }
 
-        Ok(left)
+        Ok(expr)
     }
 
     fn unary(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
-        if self.try_match(&[Bang, Minus]) {
+        if self.try_match(&[Bang, Minus, Tilde]) {
             let operator = self.previous().unwrap().clone();
             let right = self.unary()?;
             return Ok(UnaryExpr::new(operator, right));
         }
 
-        return self.primary();
+        self.power()
+    }
+
+    /// "**"右结合，且左侧比一元运算符优先级高：-2 ** 2 == -(2 ** 2)，2 ** -1 == 2 ** (-1)
+    fn power(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let expr = self.call()?;
+
+        if self.try_match(&[StarStar]) {
+            let operator = self.previous().unwrap().clone();
+            let right = self.unary()?;
+            return Ok(BinaryExpr::new(expr, operator, right));
+        }
+
+        Ok(expr)
+    }
+
+    /// 调用、下标和属性访问
+    fn call(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.try_match(&[LeftParen]) {
+                let arguments = self.arguments(&RightParen)?;
+                let paren = self.consume(&RightParen, "Expect ')' after arguments.")?.unwrap().clone();
+                expr = CallExpr::new(expr, paren, arguments);
+            } else if self.try_match(&[LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(&RightBracket, "Expect ']' after index.")?.unwrap().clone();
+                expr = IndexExpr::new(expr, bracket, index);
+            } else if self.try_match(&[Dot]) {
+                let name = self.consume(&Identifier, "Expect property name after '.'.")?.unwrap().clone();
+                expr = GetExpr::new(expr, name);
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    /// 以逗号分隔的参数或元素，允许末尾多一个逗号，end为结束的token类型
+    fn arguments(&mut self, end: &TokenType) -> Result<Vec<Box<dyn Expr>>, SyntaxError> {
+        let mut arguments = Vec::new();
+        while !self.check(end) {
+            arguments.push(self.assignment()?);
+            if !self.try_match(&[Comma]) {
+                break;
+            }
+        }
+        Ok(arguments)
     }
 
     fn primary(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
@@ -92,15 +259,63 @@ impl Parser {
             return Ok(LiteralExpr::new(Object::Nil));
         }
         if self.try_match(&[Number, String]) {
-            let value = self.previous().unwrap().clone().literal.unwrap();
+            let value = Object::clone(self.previous().unwrap().literal.as_ref().unwrap());
             return Ok(LiteralExpr::new(value));
         }
+        if self.try_match(&[Identifier]) {
+            return Ok(VariableExpr::new(self.previous().unwrap().clone()));
+        }
+        if self.try_match(&[Interpolation]) {
+            return self.interpolation();
+        }
         if self.try_match(&[LeftParen]) {
             let expr = self.expression()?;
             self.consume(&RightParen, "Expect ')'  after expression.")?;
             return Ok(GroupingExpr::new(expr));
         }
-        panic!("TODO");
+        if self.try_match(&[LeftBracket]) {
+            let elements = self.arguments(&RightBracket)?;
+            self.consume(&RightBracket, "Expect ']' after list elements.")?;
+            return Ok(ListExpr::new(elements));
+        }
+        if self.try_match(&[LeftBrace]) {
+            return self.map();
+        }
+        Err(SyntaxError::new(self.peek().unwrap().line, "Expect expression.".to_string()))
+    }
+
+    /// 表达式中的'{'总是map字面量：{key: value, ...}，允许末尾多一个逗号
+    fn map(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let brace = self.previous().unwrap().clone();
+        let mut entries = Vec::new();
+        while !self.check(&RightBrace) {
+            let key = self.assignment()?;
+            self.consume(&Colon, "Expect ':' after map key.")?;
+            let value = self.assignment()?;
+            entries.push((key, value));
+            if !self.try_match(&[Comma]) {
+                break;
+            }
+        }
+        self.consume(&RightBrace, "Expect '}' after map entries.")?;
+        Ok(MapExpr::new(brace, entries))
+    }
+
+    /// 插值字符串由若干Interpolation token和最后的String token组成，中间为插值表达式
+    fn interpolation(&mut self) -> Result<Box<dyn Expr>, SyntaxError> {
+        let mut parts: Vec<Box<dyn Expr>> = Vec::new();
+        loop {
+            let value = Object::clone(self.previous().unwrap().literal.as_ref().unwrap());
+            parts.push(LiteralExpr::new(value));
+            parts.push(self.expression()?);
+            if self.try_match(&[Interpolation]) {
+                continue;
+            }
+            self.consume(&String, "Expect '}' after interpolated expression.")?;
+            let value = Object::clone(self.previous().unwrap().literal.as_ref().unwrap());
+            parts.push(LiteralExpr::new(value));
+            return Ok(InterpolationExpr::new(parts));
+        }
     }
 
     fn try_match(&mut self, types: &[TokenType]) -> bool {
@@ -114,38 +329,50 @@ impl Parser {
     }
 
     fn advance(&mut self) -> Option<&Token> {
-        self.current += 1;
+        let next = self.next_token();
+        self.previous = self.current.replace(next);
         self.previous()
     }
 
-    fn check(&self, typ: &TokenType) -> bool {
-        match self.peek() {
-            Some(token) if token.typ == *typ => true,
-            _ => false
+    /// 读取下一个token，遇到词法错误时记录错误，并以Eof结束
+    fn next_token(&mut self) -> Token {
+        if self.error.is_none() {
+            match self.tokens.next() {
+                Some(Ok(token)) => return token,
+                Some(Err(e)) => self.error = Some(e),
+                None => {}
+            }
         }
+        let line = self.current.as_ref().map_or(1, |token| token.line);
+        Token::new(Eof, "", None, line)
+    }
+
+    fn check(&self, typ: &TokenType) -> bool {
+        matches!(self.peek(), Some(token) if token.typ == *typ)
     }
 
     fn peek(&self) -> Option<&Token> {
-        self.tokens.get(self.current)
+        self.current.as_ref()
     }
 
     fn previous(&self) -> Option<&Token> {
-        self.tokens.get(self.current - 1)
+        self.previous.as_ref()
     }
 
     fn consume(&mut self, typ: &TokenType, message: &str) -> Result<Option<&Token>, SyntaxError> {
         if self.check(typ) {
-            return Ok(self.advance());
+            Ok(self.advance())
         } else {
             let p = self.peek();
             Err(SyntaxError::new(p.unwrap().line, message.to_string()))
         }
     }
 
+    #[allow(dead_code)]
     fn synchronize(&mut self) {
         self.advance();
 
-        while let Some(t) = self.peek() {
+        while let Some(t) = self.peek().filter(|t| t.typ != Eof) {
             if self.previous().unwrap().typ == SemiColon {
                 return;
             }
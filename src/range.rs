@@ -0,0 +1,166 @@
+use std::fmt::{self, Formatter};
+use std::rc::Rc;
+use crate::error::RuntimeError;
+use crate::native::Native;
+use crate::object::Object;
+use crate::token::Token;
+
+/// to_list能转换的最大元素个数
+const MAX_LIST_LEN: i64 = 1 << 24;
+
+/// start..end或start..=end表示的整数范围，step可以为负数但不能为0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    start: i64,
+    end: i64,
+    inclusive: bool,
+    step: i64,
+}
+
+impl Range {
+    pub fn new(start: i64, end: i64, inclusive: bool) -> Self {
+        Range { start, end, inclusive, step: 1 }
+    }
+
+    /// 按step依次返回范围内的整数
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        std::iter::successors(Some(self.start), move |x| x.checked_add(self.step))
+            .take_while(move |x| self.in_bounds(*x))
+    }
+
+    fn in_bounds(&self, x: i64) -> bool {
+        match (self.step > 0, self.inclusive) {
+            (true, false) => self.start <= x && x < self.end,
+            (true, true) => self.start <= x && x <= self.end,
+            (false, false) => self.end < x && x <= self.start,
+            (false, true) => self.end <= x && x <= self.start,
+        }
+    }
+
+    /// 范围内整数的个数，超出i64时报错
+    pub fn count(&self, token: &Token) -> Result<i64, RuntimeError> {
+        let (start, end, step) = (self.start as i128, self.end as i128, self.step as i128);
+        let span = if self.step > 0 { end - start } else { start - end };
+        let span = if self.inclusive { span + 1 } else { span };
+        let count = if span <= 0 { 0 } else { (span + step.abs() - 1) / step.abs() };
+        i64::try_from(count).map_err(|_| {
+            RuntimeError::new(token.clone(), format!("Length of range {} doesn't fit in an integer.", self))
+        })
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        self.in_bounds(x) && (x as i128 - self.start as i128) % self.step as i128 == 0
+    }
+
+    /// 将范围作为长度为len的序列的切片，负数边界从末尾开始计算，返回切片中元素的位置
+    pub fn slice(&self, token: &Token, len: usize) -> Result<impl Iterator<Item = usize>, RuntimeError> {
+        if self.step <= 0 {
+            return Err(RuntimeError::new(
+                token.clone(),
+                "Slice step must be positive.".to_string(),
+            ));
+        }
+        let len = len as i64;
+        let bound = |x: i64| if x < 0 { x.saturating_add(len) } else { x };
+        let start = bound(self.start);
+        let end = bound(self.end).saturating_add(self.inclusive as i64);
+        if !(0..=len).contains(&start) || !(0..=len).contains(&end) {
+            return Err(RuntimeError::new(
+                token.clone(),
+                format!("Range {} out of range for length {}.", self, len),
+            ));
+        }
+        Ok((start as usize..end.max(start) as usize).step_by(self.step as usize))
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let operator = if self.inclusive { "..=" } else { ".." };
+        if self.step == 1 {
+            write!(f, "{}{}{}", self.start, operator, self.end)
+        } else {
+            write!(f, "({}{}{}).step({})", self.start, operator, self.end, self.step)
+        }
+    }
+}
+
+/// 获取range的方法
+pub fn method(name: &str) -> Option<Native> {
+    let method = match name {
+        "contains" => Native::new("contains", 1..=1, contains),
+        "step" => Native::new("step", 1..=1, step),
+        "len" => Native::new("len", 0..=0, len),
+        "to_list" => Native::new("to_list", 0..=0, to_list),
+        _ => return None,
+    };
+    Some(method)
+}
+
+fn range(this: &Object) -> &Range {
+    match this {
+        Object::Range(range) => range,
+        _ => unreachable!("range method bound to {}", this),
+    }
+}
+
+fn contains(_: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let x = &arguments[0];
+    Ok(Object::new_bool(x.is_int() && range(this).contains(x.int())))
+}
+
+/// 返回步长为step的新范围
+fn step(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let step = &arguments[0];
+    if !step.is_int() || step.int() == 0 {
+        return Err(RuntimeError::new(
+            token.clone(),
+            "Range step must be a non-zero integer.".to_string(),
+        ));
+    }
+    Ok(Object::Range(Rc::new(Range { step: step.int(), ..range(this).clone() })))
+}
+
+fn len(token: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(range(this).count(token)?))
+}
+
+fn to_list(token: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    let range = range(this);
+    if range.count(token)? > MAX_LIST_LEN {
+        return Err(RuntimeError::new(
+            token.clone(),
+            format!("Range {} is too large to convert to a list.", range),
+        ));
+    }
+    Ok(Object::new_list(range.iter().map(Object::Int).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::tests::eval;
+
+    #[test]
+    fn test_range() {
+        assert_eq!(eval("(0..=10).step(5).to_list()"), Ok("[0, 5, 10]".to_string()));
+        assert_eq!(eval("(10..0).step(-3).to_list()"), Ok("[10, 7, 4, 1]".to_string()));
+        assert_eq!(eval("(0..10).step(3)"), Ok("(0..10).step(3)".to_string()));
+        assert_eq!(eval("(0..10).step(3).len()"), Ok("4".to_string()));
+        assert_eq!(eval("(0..10).step(3).contains(9)"), Ok("true".to_string()));
+        assert_eq!(eval("(0..10).step(3).contains(10)"), Ok("false".to_string()));
+        assert_eq!(eval("(0..=10).contains(10.0)"), Ok("true".to_string()));
+        assert_eq!(eval("[1, 2, 3, 4][1..3]"), Ok("[2, 3]".to_string()));
+        assert_eq!(eval("[1, 2, 3, 4][1..=-1]"), Ok("[2, 3, 4]".to_string()));
+        assert_eq!(eval("[1, 2, 3, 4][(0..4).step(2)]"), Ok("[1, 3]".to_string()));
+        assert_eq!(eval("\"héllo world\"[0..5]"), Ok("\"héllo\"".to_string()));
+        assert_eq!(eval("[1, 2][1..5]"), Err("Runtime error: [line 1] Range 1..5 out of range for length 2..".to_string()));
+        assert!(eval("0..1.5").is_err());
+        assert!(eval("(0..1).step(0)").is_err());
+        assert_eq!(eval("(-9223372036854775807-1..9223372036854775806).step(2).len()"), Ok("9223372036854775807".to_string()));
+        assert!(eval("(0..=9223372036854775807).len()").is_err());
+        assert!(eval("(-1..9223372036854775807).len()").is_err());
+        assert!(eval("(-9223372036854775807-1..=9223372036854775807).len()").is_err());
+        assert!(eval("(0..100000000000).to_list()").is_err());
+        assert!(eval("(0..=9223372036854775807).to_list()").is_err());
+    }
+}
@@ -1,45 +1,60 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+use unicode_ident::{is_xid_continue, is_xid_start};
 use crate::token::{Token, TokenType};
 use crate::{SyntaxError};
 use crate::object::Object;
 
+/// 按需扫描token的词法分析器，start、current、line_start均为source中的字节下标
 pub struct Scanner {
-    source: Vec<char>,
-    tokens: Vec<Token>,
+    /// 所有token共享的源码
+    source: Rc<str>,
+    /// 最近一次scan_token得到的token
+    token: Option<Token>,
+    /// 上一个输出的token的类型
+    previous: Option<TokenType>,
+    done: bool,
     start: usize,
     current: usize,
     line: usize,
+    line_start: usize,
+    /// 每一层尚未结束的字符串插值中未闭合的'{'数量
+    interpolations: Vec<usize>,
+    /// 尚未附加到token上的文档注释
+    doc: Option<String>,
 }
 
 impl Scanner {
-    pub fn new(source: Vec<char>) -> Self {
+    /// token共享source，扫描过程中不再复制源码
+    pub fn new(source: Rc<str>) -> Self {
         Scanner {
             source,
-            tokens: Vec::new(),
+            token: None,
+            previous: None,
+            done: false,
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            interpolations: Vec::new(),
+            doc: None,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, SyntaxError> {
-        let mut had_error: Option<SyntaxError> = None;
-        while !self.is_at_end() {
-            self.start = self.current;
-            match self.scan_token() {
-                Ok(_) => {}
-                Err(e) => {
-                    //e.report("");
-                    had_error = Some(e);
-                }
+    /// 将源码解码为UTF-8字符串，忽略开头的BOM，非法的UTF-8报告在原始字节中的偏移
+    pub fn decode(source: &[u8]) -> Result<&str, SyntaxError> {
+        let stripped = source.strip_prefix("\u{FEFF}".as_bytes()).unwrap_or(source);
+        match std::str::from_utf8(stripped) {
+            Ok(source) => Ok(source),
+            Err(e) => {
+                let offset = source.len() - stripped.len() + e.valid_up_to();
+                let line = source[..offset].iter().filter(|b| **b == b'\n').count() + 1;
+                Err(SyntaxError::new(
+                    line,
+                    format!("Invalid UTF-8 at byte offset {}", offset),
+                ))
             }
         }
-
-        self.add_token_eof();
-        if let Some(e) = had_error {
-            Err(e)
-        } else {
-            Ok(&self.tokens)
-        }
     }
 
     fn scan_token(&mut self) -> Result<(), SyntaxError> {
@@ -47,14 +62,73 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace);
+            }
+            '}' => match self.interpolations.last_mut() {
+                Some(0) => {
+                    // 插值表达式结束，继续读取字符串剩余部分
+                    self.interpolations.pop();
+                    let column = self.column(self.start);
+                    let empty = self.previous == Some(TokenType::Interpolation);
+                    self.string(false)?;
+                    if empty {
+                        return Err(SyntaxError::with_column(
+                            self.line,
+                            column,
+                            "Expect expression in string interpolation".to_string(),
+                        ));
+                    }
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBrace);
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
+            '?' => self.add_token(TokenType::Question),
+            ':' => self.add_token(TokenType::Colon),
+            '.' => {
+                let token = if self.try_match('.') {
+                    if self.try_match('=') {
+                        TokenType::DotDotEqual
+                    } else {
+                        TokenType::DotDot
+                    }
+                } else {
+                    TokenType::Dot
+                };
+                self.add_token(token);
+            }
             '-' => self.add_token(TokenType::Minus),
             '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::SemiColon),
-            '*' => self.add_token(TokenType::Star),
+            '*' => {
+                let token = if self.try_match('*') {
+                    TokenType::StarStar
+                } else {
+                    TokenType::Star
+                };
+                self.add_token(token);
+            }
+            '%' => self.add_token(TokenType::Percent),
+            '~' => {
+                let token = if self.try_match('/') {
+                    TokenType::TildeSlash
+                } else {
+                    TokenType::Tilde
+                };
+                self.add_token(token);
+            }
+            '&' => self.add_token(TokenType::Ampersand),
+            '|' => self.add_token(TokenType::Pipe),
+            '^' => self.add_token(TokenType::Caret),
             '!' => {
                 let token = if self.try_match('=') {
                     TokenType::BangEqual
@@ -74,6 +148,8 @@ impl Scanner {
             '>' => {
                 let token = if self.try_match('=') {
                     TokenType::GreaterEqual
+                } else if self.try_match('>') {
+                    TokenType::GreaterGreater
                 } else {
                     TokenType::Greater
                 };
@@ -82,6 +158,8 @@ impl Scanner {
             '<' => {
                 let token = if self.try_match('=') {
                     TokenType::LessEqual
+                } else if self.try_match('<') {
+                    TokenType::LessLess
                 } else {
                     TokenType::Less
                 };
@@ -89,6 +167,8 @@ impl Scanner {
             }
             '/' => {
                 if self.try_match('/') {
+                    // "///"开头的是文档注释，"////"仍然是普通注释
+                    let is_doc = self.peek() == Some('/') && self.peek_next() != Some('/');
                     loop {
                         match self.peek() {
                             Some(ch) if ch != '\n' => {
@@ -97,16 +177,25 @@ impl Scanner {
                             _ => break,
                         }
                     }
+                    if is_doc {
+                        self.doc_comment();
+                    }
+                } else if self.try_match('*') {
+                    self.block_comment()?;
                 } else {
                     self.add_token(TokenType::Slash);
                 }
             }
             ' ' | '\r' | '\t' => {}
-            '\n' => self.line += 1,
-            '"' => self.string()?,
+            '\n' => self.new_line(),
+            '"' => self.string(false)?,
+            'r' if self.peek() == Some('"') => {
+                self.advance();
+                self.string(true)?;
+            }
             _ => {
                 if Scanner::is_digit(Some(c)) {
-                    self.number();
+                    self.number(c)?;
                 } else if Scanner::is_alpha(Some(c)) {
                     self.identifier();
                 } else {
@@ -120,15 +209,87 @@ impl Scanner {
         Ok(())
     }
 
-    fn string(&mut self) -> Result<(), SyntaxError> {
+    /// 跳过块注释，块注释可以嵌套
+    fn block_comment(&mut self) -> Result<(), SyntaxError> {
+        let (line, column) = (self.line, self.column(self.start));
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                Some('/') if self.peek_next() == Some('*') => {
+                    self.advance();
+                    self.advance();
+                    depth += 1;
+                }
+                Some('*') if self.peek_next() == Some('/') => {
+                    self.advance();
+                    self.advance();
+                    depth -= 1;
+                }
+                Some(ch) => {
+                    self.advance();
+                    if ch == '\n' {
+                        self.new_line();
+                    }
+                }
+                None => {
+                    return Err(SyntaxError::with_column(
+                        line,
+                        column,
+                        "Unterminated block comment".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 保存文档注释的内容，连续多行文档注释合并在一起，附加到下一个token上
+    fn doc_comment(&mut self) {
+        let text = &self.source[self.start + 3..self.current];
+        let text = text.strip_prefix(' ').unwrap_or(text).trim_end();
+        match &mut self.doc {
+            Some(doc) => {
+                doc.push('\n');
+                doc.push_str(text);
+            }
+            None => self.doc = Some(text.to_string()),
+        }
+    }
+
+    /// 读取字符串字面量，raw为true时不处理转义字符和插值
+    fn string(&mut self, raw: bool) -> Result<(), SyntaxError> {
+        let mut string = String::new();
+        let mut escape_error = None;
         loop {
             match self.peek() {
                 Some('"') => break,
+                Some('$') if !raw && self.peek_next() == Some('{') => {
+                    // 遇到"${"时先输出已读取的部分，插值表达式作为普通token读取
+                    self.advance();
+                    self.advance();
+                    if let Some(e) = escape_error {
+                        return Err(e);
+                    }
+                    self.interpolations.push(0);
+                    self.add_token_object(TokenType::Interpolation, Some(Object::Str(string)));
+                    return Ok(());
+                }
+                Some('\\') if !raw => {
+                    self.advance();
+                    // 转义出错时继续读到字符串结尾，避免剩余部分被当作新的token
+                    match self.escape() {
+                        Ok(ch) => string.push(ch),
+                        Err(e) => {
+                            escape_error.get_or_insert(e);
+                        }
+                    }
+                }
                 Some(ch) => {
+                    self.advance();
                     if ch == '\n' {
-                        self.line += 1;
+                        self.new_line();
                     }
-                    self.advance();
+                    string.push(ch);
                 }
                 None => {
                     return Err(SyntaxError::new(
@@ -139,64 +300,225 @@ impl Scanner {
             }
         }
         self.advance();
-        let string: String = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
+        if let Some(e) = escape_error {
+            return Err(e);
+        }
         self.add_token_object(TokenType::String, Some(Object::Str(string)));
         Ok(())
     }
 
-    fn number(&mut self) {
-        while Scanner::is_digit(self.peek()) {
+    /// 读取'\\'之后的转义序列
+    fn escape(&mut self) -> Result<char, SyntaxError> {
+        let column = self.column(self.current - '\\'.len_utf8());
+        let ch = match self.peek() {
+            Some(ch) => ch,
+            None => {
+                return Err(SyntaxError::new(
+                    self.line,
+                    "Unterminated string".to_string(),
+                ));
+            }
+        };
+        self.advance();
+        match ch {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(column),
+            _ => Err(SyntaxError::with_column(
+                self.line,
+                column,
+                format!("Unknown escape sequence '\\{}'", ch),
+            )),
+        }
+    }
+
+    /// 读取\\u{XXXX}形式的unicode转义，column为'\\'所在的列
+    fn unicode_escape(&mut self, column: usize) -> Result<char, SyntaxError> {
+        let error = |line| {
+            SyntaxError::with_column(
+                line,
+                column,
+                "Invalid unicode escape, expect '\\u{' followed by 1 to 6 hex digits and '}'".to_string(),
+            )
+        };
+        if !self.try_match('{') {
+            return Err(error(self.line));
+        }
+        let begin = self.current;
+        while matches!(self.peek(), Some(ch) if ch.is_ascii_hexdigit()) {
             self.advance();
         }
+        let digits = begin..self.current;
+        if digits.is_empty() || digits.len() > 6 || !self.try_match('}') {
+            return Err(error(self.line));
+        }
+        let digits = &self.source[digits];
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| {
+                SyntaxError::with_column(
+                    self.line,
+                    column,
+                    format!("Invalid unicode code point '\\u{{{}}}'", digits),
+                )
+            })
+    }
+
+    fn number(&mut self, first: char) -> Result<(), SyntaxError> {
+        if first == '0' {
+            let radix = match self.peek() {
+                Some('x' | 'X') => Some(16),
+                Some('o' | 'O') => Some(8),
+                Some('b' | 'B') => Some(2),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                self.advance();
+                return self.radix_number(radix);
+            }
+        }
 
+        self.digits(10)?;
+        let mut is_float = false;
         match self.peek() {
             Some('.') if Scanner::is_digit(self.peek_next()) => {
+                is_float = true;
+                self.advance();
+                self.digits(10)?;
+            }
+            _ => {}
+        }
+
+        if let Some('e' | 'E') = self.peek() {
+            is_float = true;
+            self.advance();
+            if let Some('+' | '-') = self.peek() {
                 self.advance();
-                while Scanner::is_digit(self.peek()) {
+            }
+            if !Scanner::is_digit(self.peek()) {
+                return Err(SyntaxError::new(
+                    self.line,
+                    "Expect digits after exponent in number literal".to_string(),
+                ));
+            }
+            self.digits(10)?;
+        }
+
+        let text = self.number_text(self.start);
+        let number = if is_float {
+            Object::Num(text.parse().map_err(|_| {
+                SyntaxError::new(self.line, "Invalid number literal".to_string())
+            })?)
+        } else {
+            Object::Int(text.parse().map_err(|_| {
+                SyntaxError::new(self.line, "Integer literal is too large".to_string())
+            })?)
+        };
+        self.add_token_object(TokenType::Number, Some(number));
+        Ok(())
+    }
+
+    /// 读取0x、0o、0b前缀的整数
+    fn radix_number(&mut self, radix: u32) -> Result<(), SyntaxError> {
+        self.digits(radix)?;
+        let prefix = &self.source[self.start..self.start + 2];
+        if Scanner::is_alpha_numeric(self.peek()) {
+            return Err(SyntaxError::new(
+                self.line,
+                format!("Invalid digit '{}' in '{}' number literal", self.peek().unwrap(), prefix),
+            ));
+        }
+
+        let text = self.number_text(self.start + 2);
+        if text.is_empty() {
+            return Err(SyntaxError::new(
+                self.line,
+                format!("Expect digits after '{}'", prefix),
+            ));
+        }
+        let number = i64::from_str_radix(&text, radix).map_err(|_| {
+            SyntaxError::new(self.line, "Integer literal is too large".to_string())
+        })?;
+        self.add_token_object(TokenType::Number, Some(Object::Int(number)));
+        Ok(())
+    }
+
+    /// 读取指定进制的数字，数字之间可以用单个'_'分隔
+    fn digits(&mut self, radix: u32) -> Result<(), SyntaxError> {
+        loop {
+            match self.peek() {
+                Some(ch) if ch.is_digit(radix) => {
                     self.advance();
                 }
+                Some('_')
+                    if matches!(self.previous_char(), Some(ch) if ch.is_digit(radix))
+                        && matches!(self.peek_next(), Some(ch) if ch.is_digit(radix)) =>
+                {
+                    self.advance();
+                }
+                _ => break,
             }
-            _ => {}
         }
+        if let Some('_') = self.peek() {
+            return Err(SyntaxError::new(
+                self.line,
+                "'_' must separate digits in number literal".to_string(),
+            ));
+        }
+        Ok(())
+    }
 
-        let number = self.source[self.start..self.current]
-            .iter()
-            .collect::<String>()
-            .parse()
-            .unwrap();
-        self.add_token_object(TokenType::Number, Some(Object::Num(number)));
+    /// 获取数字字面量去掉'_'分隔符后的文本，没有分隔符时直接借用源码
+    fn number_text(&self, from: usize) -> Cow<'_, str> {
+        let text = &self.source[from..self.current];
+        if text.contains('_') {
+            Cow::Owned(text.replace('_', ""))
+        } else {
+            Cow::Borrowed(text)
+        }
     }
 
     fn identifier(&mut self) {
         while Scanner::is_alpha_numeric(self.peek()) {
             self.advance();
         }
-        if let Some(typ) = Scanner::keywords(
-            &self.source[self.start..self.current]
-                .iter()
-                .collect::<String>(),
-        ) {
+        if let Some(typ) = Scanner::keywords(&self.source[self.start..self.current]) {
             self.add_token(typ);
         } else {
             self.add_token(TokenType::Identifier);
         }
     }
 
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
+
+    /// 获取source中下标为index的字符所在的列，从1开始
+    fn column(&self, index: usize) -> usize {
+        self.source[self.line_start..index].chars().count() + 1
+    }
+
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
 
     fn advance(&mut self) -> char {
-        self.current += 1;
-        *self.source.get(self.current - 1).unwrap()
+        let ch = self.peek().unwrap();
+        self.current += ch.len_utf8();
+        ch
     }
 
     fn try_match(&mut self, expected: char) -> bool {
-        match self.source.get(self.current) {
-            Some(ch) if *ch == expected => {
-                self.current += 1;
+        match self.peek() {
+            Some(ch) if ch == expected => {
+                self.current += ch.len_utf8();
                 true
             }
             _ => false,
@@ -204,11 +526,15 @@ impl Scanner {
     }
 
     fn peek(&self) -> Option<char> {
-        self.source.get(self.current).copied()
+        self.source[self.current..].chars().next()
     }
 
     fn peek_next(&self) -> Option<char> {
-        self.source.get(self.current + 1).copied()
+        self.source[self.current..].chars().nth(1)
+    }
+
+    fn previous_char(&self) -> Option<char> {
+        self.source[..self.current].chars().next_back()
     }
 
     fn add_token(&mut self, typ: TokenType) {
@@ -216,28 +542,24 @@ impl Scanner {
     }
 
     fn add_token_object(&mut self, typ: TokenType, literal: Option<Object>) {
-        let lexeme = self.source[self.start..self.current]
-            .iter()
-            .collect::<String>();
-        self.tokens
-            .push(Token::new(typ, &lexeme, literal, self.line));
-    }
-
-    fn add_token_eof(&mut self) {
-        self.tokens
-            .push(Token::new(TokenType::Eof, "", None, self.line));
+        let span = self.start..self.current;
+        let mut token = Token::with_span(typ, self.source.clone(), span, literal, self.line);
+        token.doc = self.doc.take().map(Rc::from);
+        self.token = Some(token);
     }
 
     fn is_digit(ch: Option<char>) -> bool {
         matches!(ch, Some('0'..='9'))
     }
 
+    /// 标识符的首字符，遵循Unicode XID_Start规则，另外允许'_'
     fn is_alpha(ch: Option<char>) -> bool {
-        matches!(ch, Some('a'..='z' | 'A'..='Z' | '_'))
+        matches!(ch, Some(ch) if ch == '_' || is_xid_start(ch))
     }
 
+    /// 标识符的后续字符，遵循Unicode XID_Continue规则
     fn is_alpha_numeric(ch: Option<char>) -> bool {
-        Scanner::is_alpha(ch) || Scanner::is_digit(ch)
+        matches!(ch, Some(ch) if is_xid_continue(ch))
     }
 
     fn keywords(word: &str) -> Option<TokenType> {
@@ -262,3 +584,182 @@ impl Scanner {
         }
     }
 }
+
+impl Iterator for Scanner {
+    type Item = Result<Token, SyntaxError>;
+
+    /// 扫描下一个token，出错后可以继续扫描之后的token，最后输出Eof
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.is_at_end() {
+            self.start = self.current;
+            if let Err(e) = self.scan_token() {
+                self.token = None;
+                return Some(Err(e));
+            }
+            if let Some(token) = self.token.take() {
+                self.previous = Some(token.typ.clone());
+                return Some(Ok(token));
+            }
+        }
+
+        if !self.interpolations.is_empty() {
+            self.interpolations.clear();
+            return Some(Err(SyntaxError::new(
+                self.line,
+                "Unterminated string interpolation".to_string(),
+            )));
+        }
+
+        if self.done {
+            return None;
+        }
+        self.done = true;
+        let span = self.current..self.current;
+        Some(Ok(Token::with_span(TokenType::Eof, self.source.clone(), span, None, self.line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::error::SyntaxError;
+    use crate::object::Object;
+    use crate::scanner::Scanner;
+    use crate::token::{Token, TokenType};
+
+    fn scan(source: &str) -> Result<Vec<Token>, SyntaxError> {
+        Scanner::new(Rc::from(source)).collect()
+    }
+
+    fn scan_literal(source: &str) -> Result<Object, String> {
+        match scan(source) {
+            Ok(tokens) => Ok(Object::clone(tokens[0].literal.as_ref().unwrap())),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_number_literals() {
+        assert_eq!(scan_literal("0xFF"), Ok(Object::Int(255)));
+        assert_eq!(scan_literal("0b1010"), Ok(Object::Int(10)));
+        assert_eq!(scan_literal("0o17"), Ok(Object::Int(15)));
+        assert_eq!(scan_literal("1_000_000"), Ok(Object::Int(1_000_000)));
+        assert_eq!(scan_literal("1e-9"), Ok(Object::Num(1e-9)));
+        assert_eq!(scan_literal("6.02E23"), Ok(Object::Num(6.02E23)));
+        assert_eq!(scan_literal("0xdead_beef"), Ok(Object::Int(0xdead_beef)));
+    }
+
+    #[test]
+    fn test_malformed_number_literals() {
+        for source in ["0x", "1e", "1e+", "0b102", "0xFG", "1__0", "1_", "0x_1", "0xFFFFFFFFFFFFFFFFF"] {
+            assert!(scan_literal(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let string = |s: &str| Ok(Object::Str(s.to_string()));
+        assert_eq!(scan_literal(r#""a\tb\nc""#), string("a\tb\nc"));
+        assert_eq!(scan_literal(r#""say \"hi\" \\ \0""#), string("say \"hi\" \\ \0"));
+        assert_eq!(scan_literal(r#""\u{1F600}\u{41}""#), string("\u{1F600}A"));
+        assert_eq!(scan_literal(r#"r"C:\dir\n""#), string("C:\\dir\\n"));
+    }
+
+    #[test]
+    fn test_invalid_string_escapes() {
+        assert_eq!(
+            scan_literal("1\n  \"ab\\q\""),
+            Err("Syntax error: [line 2, column 6] Unknown escape sequence '\\q'.".to_string())
+        );
+        for source in [r#""\u{}""#, r#""\u41""#, r#""\u{1234567}""#, r#""\u{D800}""#, r#""\"#] {
+            assert!(scan_literal(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        let types = scan(r#""a${1 + {2}}b${"c${3}"}""#)
+            .unwrap()
+            .iter()
+            .map(|t| t.typ.clone())
+            .collect::<Vec<_>>();
+        use TokenType::*;
+        assert_eq!(
+            types,
+            [Interpolation, Number, Plus, LeftBrace, Number, RightBrace, Interpolation,
+                Interpolation, Number, String, String, Eof]
+        );
+        assert!(scan(r#""a${1"#).is_err());
+        assert_eq!(scan_literal(r#""\${1}""#), Ok(Object::Str("${1}".to_string())));
+    }
+
+    #[test]
+    fn test_comments() {
+        let source = "/* a /* nested\n */ comment\n*/ 1 // line\n/// doc for\n///  two\n//// not doc\n2 / 3";
+        let tokens = scan(source).unwrap();
+        assert_eq!(tokens.len(), 5);
+        assert_eq!((tokens[0].line, tokens[0].doc.as_deref()), (3, None));
+        assert_eq!((tokens[1].line, tokens[1].doc.as_deref()), (7, Some("doc for\n two")));
+        assert_eq!(tokens[2].typ, TokenType::Slash);
+        assert_eq!(tokens[3].doc, None);
+
+        assert_eq!(
+            scan("1\n  /* /* */ 2").unwrap_err().to_string(),
+            "Syntax error: [line 2, column 3] Unterminated block comment."
+        );
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let source = Scanner::decode("\u{FEFF}变量 naïve _x1 ä\u{0308}".as_bytes()).unwrap();
+        let tokens = scan(source).unwrap();
+        let lexemes = tokens.iter().map(|t| t.lexeme()).collect::<Vec<_>>();
+        assert_eq!(lexemes, ["变量", "naïve", "_x1", "ä\u{0308}", ""]);
+        assert!(tokens[..4].iter().all(|t| t.typ == TokenType::Identifier));
+
+        let error = Scanner::decode(b"1 +\n\xff 2").unwrap_err();
+        assert_eq!(error.to_string(), "Syntax error: [line 2] Invalid UTF-8 at byte offset 4.");
+        let error = Scanner::decode(b"\xEF\xBB\xBF1 + \xFF").unwrap_err();
+        assert_eq!(error.to_string(), "Syntax error: [line 1] Invalid UTF-8 at byte offset 7.");
+    }
+
+    #[test]
+    fn test_iterator() {
+        let mut scanner = Scanner::new(Rc::from("1 # 2"));
+        assert_eq!(scanner.next().unwrap().unwrap().literal.as_deref(), Some(&Object::Int(1)));
+        assert!(scanner.next().unwrap().is_err());
+        assert_eq!(scanner.next().unwrap().unwrap().literal.as_deref(), Some(&Object::Int(2)));
+        assert_eq!(scanner.next().unwrap().unwrap().typ, TokenType::Eof);
+        assert!(scanner.next().is_none());
+    }
+
+    #[test]
+    fn test_tokens_share_source() {
+        let source: Rc<str> = Rc::from("1 + \"a\"");
+        let tokens = Scanner::new(source.clone()).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(Rc::strong_count(&source), 1 + tokens.len());
+        assert_eq!(tokens[2].lexeme(), "\"a\"");
+
+        let token = tokens[2].clone();
+        assert!(Rc::ptr_eq(token.literal.as_ref().unwrap(), tokens[2].literal.as_ref().unwrap()));
+    }
+
+    /// 扫描大文件的基准测试: cargo test --release bench_scan_large_input -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_scan_large_input() {
+        let line = "(count_1 + 2.5) * total / 0xFF - \"label\" >= limit // comment\n";
+        let source = line.repeat(200_000);
+        let start = std::time::Instant::now();
+        let tokens = Scanner::new(Rc::from(source.as_str())).filter(|t| t.is_ok()).count();
+        let elapsed = start.elapsed();
+        println!(
+            "scanned {} tokens from {} bytes in {:?} ({:.1} MB/s)",
+            tokens,
+            source.len(),
+            elapsed,
+            source.len() as f64 / elapsed.as_secs_f64() / 1e6
+        );
+        assert_eq!(tokens, 13 * 200_000 + 1);
+    }
+}
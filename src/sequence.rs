@@ -0,0 +1,53 @@
+//! 列表和字符串共用的下标处理，label为报错信息中的类型名，如"List"、"String"
+
+use std::ops::Range;
+use crate::error::RuntimeError;
+use crate::object::Object;
+use crate::token::Token;
+
+/// 将下标转换为[0, len)中的位置，负数下标从末尾开始计算，超出范围时报错
+pub fn index(token: &Token, label: &str, len: usize, index: &Object) -> Result<usize, RuntimeError> {
+    match position(token, label, len, index)? {
+        Some(i) if i < len => Ok(i),
+        _ => Err(out_of_range(token, label, len, index)),
+    }
+}
+
+pub fn out_of_range(token: &Token, label: &str, len: usize, index: &Object) -> RuntimeError {
+    RuntimeError::new(
+        token.clone(),
+        format!("{} index {} out of range for length {}.", label, index, len),
+    )
+}
+
+/// 将下标转换为位置，负数下标加上len，结果小于0时返回None
+pub fn position(token: &Token, label: &str, len: usize, index: &Object) -> Result<Option<usize>, RuntimeError> {
+    if !index.is_int() {
+        return Err(RuntimeError::new(
+            token.clone(),
+            format!("{} index must be an integer.", label),
+        ));
+    }
+    let i = index.int();
+    let i = if i < 0 { i.checked_add(len as i64) } else { Some(i) };
+    Ok(i.and_then(|i| usize::try_from(i).ok()))
+}
+
+/// 切片[start, end)的位置，省略start时为0，省略end时为len，超出范围的下标会被截断
+pub fn slice(
+    token: &Token,
+    label: &str,
+    len: usize,
+    start: Option<&Object>,
+    end: Option<&Object>,
+) -> Result<Range<usize>, RuntimeError> {
+    let bound = |arg: Option<&Object>, default: usize| -> Result<usize, RuntimeError> {
+        match arg {
+            Some(arg) => Ok(position(token, label, len, arg)?.unwrap_or(0).min(len)),
+            None => Ok(default),
+        }
+    };
+    let start = bound(start, 0)?;
+    let end = bound(end, len)?;
+    Ok(start..end.max(start))
+}
@@ -0,0 +1,78 @@
+use std::env;
+use std::io::{self, stdout, BufRead, Write};
+use std::rc::Rc;
+use std::sync::OnceLock;
+use crate::error::RuntimeError;
+use crate::fs::io_error;
+use crate::native::Native;
+use crate::object::Object;
+use crate::token::Token;
+
+/// 脚本路径之后的命令行参数
+static ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// 设置args()返回的脚本参数，只能设置一次
+pub fn set_args(args: Vec<String>) {
+    let _ = ARGS.set(args);
+}
+
+/// 获取io模块的成员
+pub fn member(name: &str) -> Option<Object> {
+    let native = match name {
+        "read_line" => Native::new("read_line", 0..=0, read_line),
+        "args" => Native::new("args", 0..=0, args),
+        "env" => Native::new("env", 1..=1, env),
+        "exit" => Native::new("exit", 0..=1, exit),
+        _ => return None,
+    };
+    Some(Object::Native(Rc::new(native)))
+}
+
+/// 从标准输入读取一行，去掉末尾的换行符，读到末尾时返回nil
+fn read_line(token: &Token, _: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    let mut line = String::new();
+    let n = io::stdin().lock().read_line(&mut line).map_err(|e| io_error(token, "read", "stdin", e))?;
+    if n == 0 {
+        return Ok(Object::Nil);
+    }
+    let len = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(len);
+    Ok(Object::Str(line))
+}
+
+fn args(_: &Token, _: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    let args = ARGS.get().map(|args| args.iter().cloned().map(Object::Str).collect()).unwrap_or_default();
+    Ok(Object::new_list(args))
+}
+
+/// 获取环境变量，不存在或不是合法的Unicode时返回nil
+fn env(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        Object::Str(name) => Ok(env::var(name).map(Object::Str).unwrap_or(Object::Nil)),
+        _ => Err(RuntimeError::new(token.clone(), "Argument must be a string.".to_string())),
+    }
+}
+
+/// 以code（默认为0）为退出码结束进程
+fn exit(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let code = arguments.first().unwrap_or(&Object::Int(0));
+    let code = code.is_int().then(|| i32::try_from(code.int()).ok()).flatten().ok_or_else(|| {
+        RuntimeError::new(token.clone(), "Exit code must be a 32-bit integer.".to_string())
+    })?;
+    let _ = stdout().flush();
+    std::process::exit(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::tests::eval;
+
+    #[test]
+    fn test_io() {
+        assert_eq!(eval("io.args()"), Ok("[]".to_string()));
+        assert_eq!(eval("io.env(\"LOX_TEST_UNSET_VARIABLE\")"), Ok("nil".to_string()));
+        std::env::set_var("LOX_TEST_ENV_VARIABLE", "value");
+        assert_eq!(eval("io.env(\"LOX_TEST_ENV_VARIABLE\")"), Ok("\"value\"".to_string()));
+        assert!(eval("io.exit(1.5)").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::rc::Rc;
+use crate::error::RuntimeError;
+use crate::native::Native;
+use crate::object::Object;
+use crate::sequence;
+use crate::token::Token;
+
+/// repeat生成的字符串的最大字节数
+const MAX_REPEAT_LEN: usize = 1 << 28;
+
+/// 获取字符串的方法，下标和长度都按字符（Unicode标量值）计算
+pub fn method(name: &str) -> Option<Native> {
+    let method = match name {
+        "len" => Native::new("len", 0..=0, len),
+        "upper" => Native::new("upper", 0..=0, upper),
+        "lower" => Native::new("lower", 0..=0, lower),
+        "trim" => Native::new("trim", 0..=0, trim),
+        "split" => Native::new("split", 1..=1, split),
+        "join" => Native::new("join", 1..=1, join),
+        "replace" => Native::new("replace", 2..=2, replace),
+        "find" => Native::new("find", 1..=1, find),
+        "starts_with" => Native::new("starts_with", 1..=1, starts_with),
+        "ends_with" => Native::new("ends_with", 1..=1, ends_with),
+        "substr" => Native::new("substr", 1..=2, substr),
+        "repeat" => Native::new("repeat", 1..=1, repeat),
+        "char_code" => Native::new("char_code", 0..=1, char_code),
+        _ => return None,
+    };
+    Some(method)
+}
+
+/// 获取string模块的成员
+pub fn member(name: &str) -> Option<Object> {
+    let native = match name {
+        "from_char_code" => Native::new("from_char_code", 1..=1, from_char_code),
+        _ => return None,
+    };
+    Some(Object::Native(Rc::new(native)))
+}
+
+/// 获取下标处的字符，负数下标从末尾开始计算
+pub fn index(token: &Token, string: &str, index: &Object) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(char_at(token, string, index)?.to_string()))
+}
+
+fn char_at(token: &Token, string: &str, index: &Object) -> Result<char, RuntimeError> {
+    let i = sequence::index(token, "String", string.chars().count(), index)?;
+    Ok(string.chars().nth(i).unwrap())
+}
+
+fn string(this: &Object) -> &str {
+    match this {
+        Object::Str(string) => string,
+        _ => unreachable!("string method bound to {}", this),
+    }
+}
+
+fn string_argument<'a>(token: &Token, argument: &'a Object) -> Result<&'a str, RuntimeError> {
+    match argument {
+        Object::Str(string) => Ok(string),
+        _ => Err(RuntimeError::new(token.clone(), "Argument must be a string.".to_string())),
+    }
+}
+
+fn len(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(string(this).chars().count() as i64))
+}
+
+fn upper(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(string(this).to_uppercase()))
+}
+
+fn lower(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(string(this).to_lowercase()))
+}
+
+fn trim(_: &Token, this: &Object, _: &[Object]) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(string(this).trim().to_string()))
+}
+
+/// 按分隔符拆分为字符串列表，分隔符为空字符串时拆分为单个字符
+fn split(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let separator = string_argument(token, &arguments[0])?;
+    let parts = if separator.is_empty() {
+        string(this).chars().map(|c| Object::Str(c.to_string())).collect()
+    } else {
+        string(this).split(separator).map(|s| Object::Str(s.to_string())).collect()
+    };
+    Ok(Object::new_list(parts))
+}
+
+/// 以该字符串为分隔符连接列表中的元素，非字符串元素按print的规则转换
+fn join(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let elements = match &arguments[0] {
+        Object::List(elements) => elements.borrow(),
+        _ => return Err(RuntimeError::new(token.clone(), "Argument must be a list.".to_string())),
+    };
+    let parts = elements.iter().map(|e| e.stringify()).collect::<Vec<_>>();
+    Ok(Object::Str(parts.join(string(this))))
+}
+
+fn replace(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let from = string_argument(token, &arguments[0])?;
+    let to = string_argument(token, &arguments[1])?;
+    Ok(Object::Str(string(this).replace(from, to)))
+}
+
+/// 返回子串第一次出现的字符下标，找不到时返回-1
+fn find(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let string = string(this);
+    let sub = string_argument(token, &arguments[0])?;
+    let i = string.find(sub).map_or(-1, |i| string[..i].chars().count() as i64);
+    Ok(Object::Int(i))
+}
+
+fn starts_with(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let prefix = string_argument(token, &arguments[0])?;
+    Ok(Object::new_bool(string(this).starts_with(prefix)))
+}
+
+fn ends_with(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let suffix = string_argument(token, &arguments[0])?;
+    Ok(Object::new_bool(string(this).ends_with(suffix)))
+}
+
+/// substr(start, end)返回[start, end)之间的字符组成的子串，与列表的slice相同，超出范围的下标会被截断
+fn substr(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let chars = string(this).chars().collect::<Vec<_>>();
+    let range = sequence::slice(token, "String", chars.len(), arguments.first(), arguments.get(1))?;
+    Ok(Object::Str(chars[range].iter().collect()))
+}
+
+fn repeat(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let count = &arguments[0];
+    if !count.is_int() || count.int() < 0 {
+        return Err(RuntimeError::new(
+            token.clone(),
+            "Repeat count must be a non-negative integer.".to_string(),
+        ));
+    }
+    let string = string(this);
+    match string.len().checked_mul(count.int() as usize) {
+        Some(len) if len <= MAX_REPEAT_LEN => Ok(Object::Str(string.repeat(count.int() as usize))),
+        _ => Err(RuntimeError::new(token.clone(), "Repeated string is too long.".to_string())),
+    }
+}
+
+/// 返回下标处（默认为0）字符的码点
+fn char_code(token: &Token, this: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let index = arguments.first().unwrap_or(&Object::Int(0));
+    Ok(Object::Int(char_at(token, string(this), index)? as i64))
+}
+
+/// 由码点创建只含一个字符的字符串
+fn from_char_code(token: &Token, _: &Object, arguments: &[Object]) -> Result<Object, RuntimeError> {
+    let code = &arguments[0];
+    code.is_int()
+        .then(|| u32::try_from(code.int()).ok().and_then(char::from_u32))
+        .flatten()
+        .map(|c| Object::Str(c.to_string()))
+        .ok_or_else(|| RuntimeError::new(
+            token.clone(),
+            format!("Invalid char code {}.", code),
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::tests::eval;
+
+    #[test]
+    fn test_string_methods() {
+        assert_eq!(eval("\"héllo\".len()"), Ok("5".to_string()));
+        assert_eq!(eval("[\"Ab\".upper(), \"Ab\".lower(), \"  a b \".trim()]"), Ok("[\"AB\", \"ab\", \"a b\"]".to_string()));
+        assert_eq!(eval("\"a,b,,c\".split(\",\")"), Ok("[\"a\", \"b\", \"\", \"c\"]".to_string()));
+        assert_eq!(eval("\"hé\".split(\"\")"), Ok("[\"h\", \"é\"]".to_string()));
+        assert_eq!(eval("\", \".join([1, \"a\", nil])"), Ok("\"1, a, nil\"".to_string()));
+        assert_eq!(eval("\"aXbX\".replace(\"X\", \"-\")"), Ok("\"a-b-\"".to_string()));
+        assert_eq!(eval("[\"日本語\".find(\"語\"), \"abc\".find(\"z\")]"), Ok("[2, -1]".to_string()));
+        assert_eq!(eval("[\"hello\".starts_with(\"he\"), \"hello\".ends_with(\"he\")]"), Ok("[true, false]".to_string()));
+        assert_eq!(eval("[\"日本語\".substr(1), \"日本語\".substr(0, -1), \"abc\".substr(2, 1)]"), Ok("[\"本語\", \"日本\", \"\"]".to_string()));
+        assert_eq!(eval("[\"日本語\"[1], \"日本語\"[-1]]"), Ok("[\"本\", \"語\"]".to_string()));
+        assert_eq!(eval("\"ab\".repeat(3)"), Ok("\"ababab\"".to_string()));
+        assert_eq!(eval("[\"a\".char_code(), \"aé\".char_code(1)]"), Ok("[97, 233]".to_string()));
+        assert_eq!(eval("string.from_char_code(26085)"), Ok("\"日\"".to_string()));
+        assert_eq!(eval("\"abc\"[3]"), Err("Runtime error: [line 1] String index 3 out of range for length 3..".to_string()));
+        assert!(eval("\"abc\"[0.5]").is_err());
+        assert!(eval("\"\".char_code()").is_err());
+        assert!(eval("\"a\".repeat(-1)").is_err());
+        assert!(eval("\"ab\".repeat(9223372036854775807)").is_err());
+        assert!(eval("\"a\".repeat(9223372036854775807)").is_err());
+        assert_eq!(eval("\"\".repeat(9223372036854775807)"), Ok("\"\"".to_string()));
+        assert!(eval("\"a\".split(1)").is_err());
+        assert!(eval("string.from_char_code(55296)").is_err());
+    }
+}
@@ -1,23 +1,60 @@
 use std::fmt::{self, Formatter};
+use std::ops::Range;
+use std::rc::Rc;
 use crate::object::Object;
 
-#[derive(Debug, Clone)]
+/// token不单独保存lexeme，而是保存其在共享源码中的字节范围
+///
+/// 字面量和文档注释也通过Rc共享，复制token（如构造运行时错误时）不需要分配内存
+#[derive(Clone)]
 pub struct Token {
     pub typ: TokenType,
-    pub lexeme: String,
-    pub literal: Option<Object>,
+    source: Rc<str>,
+    pub span: Range<usize>,
+    pub literal: Option<Rc<Object>>,
     pub line: usize,
+    /// 紧接在token之前的文档注释，供工具使用
+    pub doc: Option<Rc<str>>,
 }
 
 impl Token {
     pub fn new(typ: TokenType, lexeme: &str, literal: Option<Object>, line: usize) -> Self {
+        Token::with_span(typ, Rc::from(lexeme), 0..lexeme.len(), literal, line)
+    }
+
+    pub fn with_span(
+        typ: TokenType,
+        source: Rc<str>,
+        span: Range<usize>,
+        literal: Option<Object>,
+        line: usize,
+    ) -> Self {
         Token {
             typ,
-            lexeme: lexeme.to_string(),
-            literal,
+            source,
+            span,
+            literal: literal.map(Rc::new),
             line,
+            doc: None,
         }
     }
+
+    /// 获取token在源码中对应的文本
+    pub fn lexeme(&self) -> &str {
+        &self.source[self.span.clone()]
+    }
+}
+
+/// 不打印整个共享源码，只打印token本身
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("typ", &self.typ)
+            .field("lexeme", &self.lexeme())
+            .field("line", &self.line)
+            .field("literal", &self.literal)
+            .finish()
+    }
 }
 
 impl fmt::Display for Token {
@@ -26,13 +63,17 @@ impl fmt::Display for Token {
             f,
             "{:?} {} {}",
             self.typ,
-            self.lexeme,
+            self.lexeme(),
             if let Some(literal) = &self.literal {
                 literal.to_string()
             } else {
                 "None".to_string()
             }
-        )
+        )?;
+        if let Some(doc) = &self.doc {
+            write!(f, " /// {:?}", doc)?;
+        }
+        Ok(())
     }
 }
 
@@ -42,23 +83,39 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
+    DotDot,
+    DotDotEqual,
+    Question,
+    Colon,
     Minus,
     Plus,
     SemiColon,
     Slash,
     Star,
+    StarStar,
+    Percent,
+    TildeSlash,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
     Bang,
     BangEqual,
     Equal,
     EqualEqual,
     Greater,
     GreaterEqual,
+    GreaterGreater,
     Less,
     LessEqual,
+    LessLess,
     Identifier,
     String,
+    Interpolation,
     Number,
     And,
     Class,
@@ -78,3 +135,20 @@ pub enum TokenType {
     While,
     Eof,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::object::Object;
+    use crate::token::{Token, TokenType};
+
+    #[test]
+    fn test_debug_omits_source() {
+        let source: Rc<str> = Rc::from(format!("1 + {}", "x".repeat(1000)));
+        let token = Token::with_span(TokenType::Number, source, 0..1, Some(Object::Int(1)), 1);
+        assert_eq!(
+            format!("{:?}", token),
+            "Token { typ: Number, lexeme: \"1\", line: 1, literal: Some(Int(1)) }"
+        );
+    }
+}